@@ -1,32 +1,255 @@
-use crate::token::TokenType;
+use std::fmt;
+
+use crate::token::{Span, TokenType};
 
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
+/// A statement together with the source it was parsed from.
+///
+/// Spans are not part of a node's identity: two nodes compare equal when
+/// their kinds do, wherever they came from.
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    // Only the parser's tests read statement spans so far.
+    #[allow(dead_code)]
+    pub span: Span,
+}
+
 #[derive(Debug, PartialEq, Clone)]
-pub enum Statement {
+pub enum StatementKind {
     LetStatement(Identifier, Expression),
     ReturnStatement(Expression),
     ExpressionStatement(Expression),
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Identifier(pub String);
+/// The statements between a pair of braces; `span` covers the braces too.
+#[derive(Debug, Clone)]
+pub struct BlockStatement {
+    pub statements: Vec<Statement>,
+    // Only the parser's tests read block spans so far.
+    #[allow(dead_code)]
+    pub span: Span,
+}
+
+/// A name in a `let`, a parameter list or an expression, compared by name only.
+#[derive(Debug, Clone)]
+pub struct Identifier {
+    pub name: String,
+    pub span: Span,
+}
+
+/// An expression together with the source it was parsed from, compared by
+/// kind only like [`Statement`].
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expression {
+pub enum ExpressionKind {
     Identifier(Identifier),
     IntegerLiteral(i64),
+    FloatLiteral(f64),
+    Boolean(bool),
+    StringLiteral(String),
+    ArrayLiteral(Vec<Expression>),
+    HashLiteral(Vec<(Expression, Expression)>),
+    Index {
+        left: Box<Expression>,
+        index: Box<Expression>,
+    },
     Prefix(TokenType, Box<Expression>),
+    Infix(Box<Expression>, TokenType, Box<Expression>),
+    /// Compound assignment such as `x += 1`; `operator` is the `+=` token.
+    Assign {
+        name: Identifier,
+        operator: TokenType,
+        value: Box<Expression>,
+    },
+    If {
+        condition: Box<Expression>,
+        consequence: BlockStatement,
+        alternative: Option<BlockStatement>,
+    },
+    Function {
+        params: Vec<Identifier>,
+        body: BlockStatement,
+    },
+    Call {
+        function: Box<Expression>,
+        args: Vec<Expression>,
+    },
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Copy)]
 pub enum Precedence {
     LOWEST,
-    EQUALS,  // == LESSGREATER // > or <
-    SUM,     // +
-    PRODUCT, // *
-    PREFIX,  // -X or !X
-    CALL,    // myFunction(X)
+    ASSIGN,      // +=
+    OR,          // ||
+    AND,         // &&
+    EQUALS,      // ==
+    LESSGREATER, // > or <=
+    SUM,         // +
+    PRODUCT,     // *
+    PREFIX,      // -X or !X
+    CALL,        // myFunction(X)
+    INDEX,       // array[index]
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Statement {
+        Statement { kind, span }
+    }
+}
+
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl From<StatementKind> for Statement {
+    fn from(kind: StatementKind) -> Self {
+        Statement::new(kind, Span::default())
+    }
+}
+
+impl PartialEq for BlockStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.statements == other.statements
+    }
+}
+
+impl From<Vec<Statement>> for BlockStatement {
+    fn from(statements: Vec<Statement>) -> Self {
+        BlockStatement {
+            statements,
+            span: Span::default(),
+        }
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl From<&str> for Identifier {
+    fn from(name: &str) -> Self {
+        Identifier {
+            name: name.to_string(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Expression {
+        Expression { kind, span }
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Expression::new(kind, Span::default())
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for statement in &self.statements {
+            write!(f, "{}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            StatementKind::LetStatement(ident, value) => write!(f, "let {} = {};", ident, value),
+            StatementKind::ReturnStatement(value) => write!(f, "return {};", value),
+            StatementKind::ExpressionStatement(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{ ")?;
+        for statement in &self.statements {
+            write!(f, "{} ", statement)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ExpressionKind::Identifier(ident) => write!(f, "{}", ident),
+            ExpressionKind::IntegerLiteral(value) => write!(f, "{}", value),
+            ExpressionKind::FloatLiteral(value) => write!(f, "{:?}", value),
+            ExpressionKind::Boolean(value) => write!(f, "{}", value),
+            ExpressionKind::StringLiteral(value) => write!(f, "{:?}", value),
+            ExpressionKind::ArrayLiteral(elements) => write!(f, "[{}]", join(elements)),
+            ExpressionKind::HashLiteral(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            ExpressionKind::Index { left, index } => write!(f, "({}[{}])", left, index),
+            ExpressionKind::Prefix(operator, right) => write!(f, "({}{})", operator, right),
+            ExpressionKind::Infix(left, operator, right) => {
+                write!(f, "({} {} {})", left, operator, right)
+            }
+            ExpressionKind::Assign {
+                name,
+                operator,
+                value,
+            } => write!(f, "({} {} {})", name, operator, value),
+            ExpressionKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                write!(f, "if {} {}", condition, consequence)?;
+                if let Some(alternative) = alternative {
+                    write!(f, " else {}", alternative)?;
+                }
+                Ok(())
+            }
+            ExpressionKind::Function { params, body } => {
+                write!(f, "fn({}) {}", join(params), body)
+            }
+            ExpressionKind::Call { function, args } => write!(f, "{}({})", function, join(args)),
+        }
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
 }
@@ -0,0 +1,227 @@
+use std::fmt::{self, Write};
+
+use crate::token::Span;
+
+pub const UNEXPECTED_TOKEN: &str = "E0001";
+pub const EXPECTED_EXPRESSION: &str = "E0002";
+pub const UNCLOSED_DELIMITER: &str = "E0003";
+pub const INVALID_LITERAL: &str = "E0004";
+pub const INVALID_CHARACTER: &str = "E0005";
+pub const UNTERMINATED_STRING: &str = "E0006";
+pub const INVALID_ESCAPE: &str = "E0007";
+pub const UNTERMINATED_COMMENT: &str = "E0008";
+pub const INVALID_ASSIGNMENT: &str = "E0009";
+pub const RUNTIME_ERROR: &str = "E0010";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    // Nothing reports warnings yet; rendering already supports them.
+    #[allow(dead_code)]
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in the source, rendered by [`Diagnostic::render`] as an
+/// annotated excerpt of the offending lines.
+///
+/// A label on the primary span is drawn with `^` carets; any other label is
+/// drawn with `-` underneath its own span.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>, span: Span) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Renders the diagnostic against `source`, which `name` identifies in
+    /// the location line (a file path, `<repl>`, ...).
+    pub fn render(&self, name: &str, source: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}[{}]: {}", self.severity, self.code, self.message);
+
+        // Synthesised nodes have no position to point at.
+        if self.span.line == 0 {
+            for note in &self.notes {
+                let _ = writeln!(out, " = note: {}", note);
+            }
+            return out;
+        }
+
+        let primary_message = self
+            .labels
+            .iter()
+            .find(|label| label.span == self.span)
+            .map_or("", |label| label.message.as_str());
+        let mut annotations = vec![(self.span, '^', primary_message)];
+        for label in &self.labels {
+            if label.span != self.span && label.span.line != 0 {
+                annotations.push((label.span, '-', label.message.as_str()));
+            }
+        }
+        annotations.sort_by_key(|(span, _, _)| (span.line, span.column));
+
+        let last_line = annotations.iter().map(|(span, _, _)| span.line).max();
+        let gutter = last_line.unwrap_or(0).to_string().len();
+        let lines: Vec<&str> = source.lines().collect();
+
+        let _ = writeln!(
+            out,
+            "{:gutter$}--> {}:{}:{}",
+            "", name, self.span.line, self.span.column
+        );
+        let _ = writeln!(out, "{:gutter$} |", "");
+
+        let mut current_line = None;
+        for (span, marker, message) in annotations {
+            let text = lines.get(span.line - 1).copied().unwrap_or("");
+            if current_line != Some(span.line) {
+                let _ = writeln!(out, "{:>gutter$} | {}", span.line, text);
+                current_line = Some(span.line);
+            }
+
+            // Keep tabs so the underline lines up with the source above it.
+            let indent: String = text
+                .chars()
+                .take(span.column - 1)
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            let width = source
+                .get(span.start..span.end)
+                .and_then(|text| text.lines().next())
+                .map_or(0, |text| text.chars().count())
+                .max(1);
+            let underline = marker.to_string().repeat(width);
+            let line = format!("{:gutter$} | {}{} {}", "", indent, underline, message);
+            let _ = writeln!(out, "{}", line.trim_end());
+        }
+
+        for note in &self.notes {
+            let _ = writeln!(out, "{:gutter$} = note: {}", "", note);
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}[{}]: {}",
+            self.span.line, self.span.column, self.severity, self.code, self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token::Span;
+
+    use super::{Diagnostic, UNCLOSED_DELIMITER, UNEXPECTED_TOKEN};
+
+    fn span(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn render_primary_label() {
+        let source = "let x = 1;\nlet y 5;\n";
+        let diagnostic = Diagnostic::error(
+            UNEXPECTED_TOKEN,
+            "expected `=`, found `5`",
+            span(17, 18, 2, 7),
+        )
+        .with_label(span(17, 18, 2, 7), "expected `=`")
+        .with_note("a let statement binds a name with `let name = value;`");
+
+        let expected = "\
+error[E0001]: expected `=`, found `5`
+ --> test.cr:2:7
+  |
+2 | let y 5;
+  |       ^ expected `=`
+  = note: a let statement binds a name with `let name = value;`
+";
+
+        assert_eq!(diagnostic.render("test.cr", source), expected);
+    }
+
+    #[test]
+    fn render_secondary_label_on_another_line() {
+        let source = "if (x) {\n\tx + 1\n";
+        let diagnostic =
+            Diagnostic::error(UNCLOSED_DELIMITER, "unclosed block", span(16, 16, 2, 7))
+                .with_label(span(7, 8, 1, 8), "block opened here")
+                .with_label(span(16, 16, 2, 7), "expected `}`");
+
+        let expected = "\
+error[E0003]: unclosed block
+ --> <repl>:2:7
+  |
+1 | if (x) {
+  |        - block opened here
+2 | \tx + 1
+  | \t     ^ expected `}`
+";
+
+        assert_eq!(diagnostic.render("<repl>", source), expected);
+    }
+
+    #[test]
+    fn render_multi_character_span() {
+        let source = "foobar + 1";
+        let diagnostic = Diagnostic::error(UNEXPECTED_TOKEN, "unexpected", span(0, 6, 1, 1));
+
+        let rendered = diagnostic.render("<repl>", source);
+
+        assert!(rendered.contains("  | ^^^^^^\n"), "{}", rendered);
+    }
+}
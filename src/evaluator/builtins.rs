@@ -0,0 +1,132 @@
+use super::object::{Builtin, Object};
+
+/// Native functions available in every program, looked up by name once the
+/// environment has no binding for an identifier.
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "len",
+        function: len,
+    },
+    Builtin {
+        name: "first",
+        function: first,
+    },
+    Builtin {
+        name: "last",
+        function: last,
+    },
+    Builtin {
+        name: "rest",
+        function: rest,
+    },
+    Builtin {
+        name: "push",
+        function: push,
+    },
+    Builtin {
+        name: "puts",
+        function: puts,
+    },
+    Builtin {
+        name: "type",
+        function: type_of,
+    },
+];
+
+pub fn lookup(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name == name)
+        .map(Object::Builtin)
+}
+
+fn len(args: Vec<Object>) -> Object {
+    if let Err(error) = check_arity(&args, 1) {
+        return error;
+    }
+    let length = match &args[0] {
+        Object::String(value) => value.chars().count(),
+        Object::Array(elements) => elements.len(),
+        Object::Hash(pairs) => pairs.len(),
+        other => return unsupported_argument("len", other),
+    };
+    Object::Integer(length as i64)
+}
+
+fn first(args: Vec<Object>) -> Object {
+    match array_argument("first", &args) {
+        Ok(elements) => elements.first().cloned().unwrap_or(Object::Null),
+        Err(error) => error,
+    }
+}
+
+fn last(args: Vec<Object>) -> Object {
+    match array_argument("last", &args) {
+        Ok(elements) => elements.last().cloned().unwrap_or(Object::Null),
+        Err(error) => error,
+    }
+}
+
+fn rest(args: Vec<Object>) -> Object {
+    match array_argument("rest", &args) {
+        Ok([]) => Object::Null,
+        Ok([_, rest @ ..]) => Object::Array(rest.to_vec()),
+        Err(error) => error,
+    }
+}
+
+fn push(args: Vec<Object>) -> Object {
+    if let Err(error) = check_arity(&args, 2) {
+        return error;
+    }
+    match &args[0] {
+        Object::Array(elements) => {
+            let mut elements = elements.clone();
+            elements.push(args[1].clone());
+            Object::Array(elements)
+        }
+        other => unsupported_argument("push", other),
+    }
+}
+
+fn puts(args: Vec<Object>) -> Object {
+    for arg in &args {
+        println!("{}", arg);
+    }
+    Object::Null
+}
+
+fn type_of(args: Vec<Object>) -> Object {
+    if let Err(error) = check_arity(&args, 1) {
+        return error;
+    }
+    Object::String(args[0].type_name().to_string())
+}
+
+fn check_arity(args: &[Object], expected: usize) -> Result<(), Object> {
+    if args.len() == expected {
+        return Ok(());
+    }
+    Err(Object::error(format!(
+        "wrong number of arguments: expected {}, got {}",
+        expected,
+        args.len()
+    )))
+}
+
+/// Checks that `args` is a single array and returns its elements.
+fn array_argument<'a>(name: &str, args: &'a [Object]) -> Result<&'a [Object], Object> {
+    check_arity(args, 1)?;
+    match &args[0] {
+        Object::Array(elements) => Ok(elements),
+        other => Err(unsupported_argument(name, other)),
+    }
+}
+
+fn unsupported_argument(name: &str, arg: &Object) -> Object {
+    Object::error(format!(
+        "argument to `{}` not supported, got {}",
+        name,
+        arg.type_name()
+    ))
+}
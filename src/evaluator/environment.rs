@@ -0,0 +1,57 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::object::Object;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    store: HashMap<String, Object>,
+    outer: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Rc<RefCell<Environment>> {
+        Rc::new(RefCell::new(Environment::default()))
+    }
+
+    /// Creates a scope whose lookups fall back to `outer`, used for function calls.
+    pub fn new_enclosed(outer: Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+        Rc::new(RefCell::new(Environment {
+            store: HashMap::new(),
+            outer: Some(outer),
+        }))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self
+                .outer
+                .as_ref()
+                .and_then(|outer| outer.borrow().get(name)),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: Object) {
+        self.store.insert(name.to_string(), value);
+    }
+
+    /// Bindings made directly in this scope, in no particular order.
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Object)> {
+        self.store
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Rebinds `name` in the innermost scope that defines it, returning
+    /// `false` if no scope does.
+    pub fn assign(&mut self, name: &str, value: Object) -> bool {
+        if let Some(slot) = self.store.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
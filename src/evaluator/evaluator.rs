@@ -0,0 +1,1125 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    ast::ast::{
+        BlockStatement, Expression, ExpressionKind, Identifier, Program, Statement, StatementKind,
+    },
+    token::TokenType,
+};
+
+use super::{
+    builtins,
+    environment::Environment,
+    object::{Function, HashKey, Object, RuntimeError},
+};
+
+/// How deeply expressions may nest while being evaluated, counting every
+/// expression on the way through function calls and blocks. Deeper recursion
+/// is reported as a runtime error instead of overflowing the native stack.
+pub const MAX_DEPTH: usize = 20_000;
+
+/// A native stack with room for [`MAX_DEPTH`] nested expressions even in
+/// debug builds, where each one takes around 6 KiB. Programs are evaluated
+/// on a thread of this size.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+pub fn eval_program(program: &Program, env: &Rc<RefCell<Environment>>) -> Object {
+    let mut result = Object::Null;
+
+    for statement in &program.statements {
+        result = eval_statement(statement, env, 0);
+        match result {
+            Object::ReturnValue(value) => return *value,
+            Object::Error(_) => return result,
+            _ => (),
+        }
+    }
+
+    result
+}
+
+// Unlike `eval_program`, a block keeps return values wrapped so that they
+// propagate out of nested blocks up to the enclosing function or program.
+fn eval_block_statement(
+    block: &BlockStatement,
+    env: &Rc<RefCell<Environment>>,
+    depth: usize,
+) -> Object {
+    let mut result = Object::Null;
+
+    for statement in &block.statements {
+        result = eval_statement(statement, env, depth);
+        if matches!(result, Object::ReturnValue(_) | Object::Error(_)) {
+            return result;
+        }
+    }
+
+    result
+}
+
+fn eval_statement(statement: &Statement, env: &Rc<RefCell<Environment>>, depth: usize) -> Object {
+    match &statement.kind {
+        StatementKind::LetStatement(Identifier { name, .. }, expression) => {
+            let value = eval_expression(expression, env, depth);
+            if value.is_error() {
+                return value;
+            }
+            env.borrow_mut().set(name, value);
+            Object::Null
+        }
+        StatementKind::ReturnStatement(expression) => {
+            let value = eval_expression(expression, env, depth);
+            if value.is_error() {
+                return value;
+            }
+            Object::ReturnValue(Box::new(value))
+        }
+        StatementKind::ExpressionStatement(expression) => eval_expression(expression, env, depth),
+    }
+}
+
+fn eval_expression(
+    expression: &Expression,
+    env: &Rc<RefCell<Environment>>,
+    depth: usize,
+) -> Object {
+    let result = if depth >= MAX_DEPTH {
+        Object::error("maximum call depth exceeded")
+    } else {
+        eval_expression_kind(expression, env, depth + 1)
+    };
+
+    match result {
+        // Errors from helpers that never see the AST point at the innermost
+        // expression that failed.
+        Object::Error(mut error) if error.span.line == 0 => {
+            error.span = expression.span;
+            Object::Error(error)
+        }
+        result => result,
+    }
+}
+
+fn eval_expression_kind(
+    expression: &Expression,
+    env: &Rc<RefCell<Environment>>,
+    depth: usize,
+) -> Object {
+    match &expression.kind {
+        ExpressionKind::Identifier(identifier) => eval_identifier(identifier, env),
+        ExpressionKind::IntegerLiteral(value) => Object::Integer(*value),
+        ExpressionKind::FloatLiteral(value) => Object::Float(*value),
+        ExpressionKind::Boolean(value) => Object::Boolean(*value),
+        ExpressionKind::StringLiteral(value) => Object::String(value.clone()),
+        ExpressionKind::ArrayLiteral(elements) => match eval_expressions(elements, env, depth) {
+            Ok(elements) => Object::Array(elements),
+            Err(error) => error,
+        },
+        ExpressionKind::HashLiteral(pairs) => eval_hash_literal(pairs, env, depth),
+        ExpressionKind::Index { left, index } => {
+            let left = eval_expression(left, env, depth);
+            if left.is_error() {
+                return left;
+            }
+            let index = eval_expression(index, env, depth);
+            if index.is_error() {
+                return index;
+            }
+            eval_index_expression(left, index)
+        }
+        ExpressionKind::Prefix(operator, right) => {
+            let right = eval_expression(right, env, depth);
+            if right.is_error() {
+                return right;
+            }
+            eval_prefix_expression(operator, right)
+        }
+        ExpressionKind::Infix(left, operator @ (TokenType::AND | TokenType::OR), right) => {
+            let left = eval_expression(left, env, depth);
+            if left.is_error() {
+                return left;
+            }
+            // `&&` and `||` skip the right operand once the result is known.
+            if is_truthy(&left) == (*operator == TokenType::OR) {
+                return Object::Boolean(is_truthy(&left));
+            }
+            let right = eval_expression(right, env, depth);
+            if right.is_error() {
+                return right;
+            }
+            Object::Boolean(is_truthy(&right))
+        }
+        ExpressionKind::Infix(left, operator, right) => {
+            let left = eval_expression(left, env, depth);
+            if left.is_error() {
+                return left;
+            }
+            let right = eval_expression(right, env, depth);
+            if right.is_error() {
+                return right;
+            }
+            eval_infix_expression(operator, left, right)
+        }
+        ExpressionKind::Assign {
+            name: Identifier { name, span },
+            operator,
+            value,
+        } => {
+            let current = match env.borrow().get(name) {
+                Some(current) => current,
+                None => {
+                    return Object::Error(RuntimeError {
+                        message: format!("identifier not found: {}", name),
+                        span: *span,
+                    })
+                }
+            };
+            let value = eval_expression(value, env, depth);
+            if value.is_error() {
+                return value;
+            }
+            let result = eval_infix_expression(&compound_operator(operator), current, value);
+            if result.is_error() {
+                return result;
+            }
+            env.borrow_mut().assign(name, result.clone());
+            result
+        }
+        ExpressionKind::If {
+            condition,
+            consequence,
+            alternative,
+        } => {
+            let condition = eval_expression(condition, env, depth);
+            if condition.is_error() {
+                return condition;
+            }
+            if is_truthy(&condition) {
+                eval_block_statement(consequence, env, depth)
+            } else if let Some(alternative) = alternative {
+                eval_block_statement(alternative, env, depth)
+            } else {
+                Object::Null
+            }
+        }
+        ExpressionKind::Function { params, body } => Object::Function(Function {
+            parameters: params.clone(),
+            body: body.clone(),
+            env: Rc::clone(env),
+        }),
+        ExpressionKind::Call { function, args } => {
+            let function = eval_expression(function, env, depth);
+            if function.is_error() {
+                return function;
+            }
+            match eval_expressions(args, env, depth) {
+                Ok(args) => apply_function(function, args, depth),
+                Err(error) => error,
+            }
+        }
+    }
+}
+
+/// Evaluates `expressions` left to right, stopping at the first error.
+fn eval_expressions(
+    expressions: &[Expression],
+    env: &Rc<RefCell<Environment>>,
+    depth: usize,
+) -> Result<Vec<Object>, Object> {
+    let mut evaluated = Vec::with_capacity(expressions.len());
+    for expression in expressions {
+        let value = eval_expression(expression, env, depth);
+        if value.is_error() {
+            return Err(value);
+        }
+        evaluated.push(value);
+    }
+    Ok(evaluated)
+}
+
+fn eval_hash_literal(
+    pairs: &[(Expression, Expression)],
+    env: &Rc<RefCell<Environment>>,
+    depth: usize,
+) -> Object {
+    let mut hash = BTreeMap::new();
+
+    for (key, value) in pairs {
+        let key = eval_expression(key, env, depth);
+        if key.is_error() {
+            return key;
+        }
+        let Some(key) = key.hash_key() else {
+            return Object::error(format!("unusable as hash key: {}", key.type_name()));
+        };
+        let value = eval_expression(value, env, depth);
+        if value.is_error() {
+            return value;
+        }
+        hash.insert(key, value);
+    }
+
+    Object::Hash(hash)
+}
+
+fn apply_function(function: Object, args: Vec<Object>, depth: usize) -> Object {
+    let function = match function {
+        Object::Function(function) => function,
+        Object::Builtin(builtin) => return (builtin.function)(args),
+        other => return Object::error(format!("not a function: {}", other.type_name())),
+    };
+
+    if function.parameters.len() != args.len() {
+        return Object::error(format!(
+            "wrong number of arguments: expected {}, got {}",
+            function.parameters.len(),
+            args.len()
+        ));
+    }
+
+    let env = Environment::new_enclosed(Rc::clone(&function.env));
+    for (Identifier { name, .. }, arg) in function.parameters.iter().zip(args) {
+        env.borrow_mut().set(name, arg);
+    }
+
+    match eval_block_statement(&function.body, &env, depth) {
+        Object::ReturnValue(value) => *value,
+        result => result,
+    }
+}
+
+fn eval_identifier(Identifier { name, .. }: &Identifier, env: &Rc<RefCell<Environment>>) -> Object {
+    match env.borrow().get(name).or_else(|| builtins::lookup(name)) {
+        Some(value) => value,
+        None => Object::error(format!("identifier not found: {}", name)),
+    }
+}
+
+fn eval_index_expression(left: Object, index: Object) -> Object {
+    match (&left, &index) {
+        (Object::Array(elements), Object::Integer(i)) => {
+            match usize::try_from(*i).ok().and_then(|i| elements.get(i)) {
+                Some(element) => element.clone(),
+                None => Object::error(format!(
+                    "index out of range: {} (length {})",
+                    i,
+                    elements.len()
+                )),
+            }
+        }
+        (Object::Array(_), _) => Object::error(format!(
+            "index must be an INTEGER, got {}",
+            index.type_name()
+        )),
+        // A missing key is `null` rather than an error so scripts can probe a hash.
+        (Object::Hash(pairs), _) => match index.hash_key() {
+            Some(key) => pairs.get(&key).cloned().unwrap_or(Object::Null),
+            None => Object::error(format!("unusable as hash key: {}", index.type_name())),
+        },
+        _ => Object::error(format!(
+            "index operator not supported: {}",
+            left.type_name()
+        )),
+    }
+}
+
+fn eval_prefix_expression(operator: &TokenType, right: Object) -> Object {
+    match operator {
+        TokenType::BANG => Object::Boolean(!is_truthy(&right)),
+        TokenType::MINUS => match right {
+            Object::Integer(value) => match value.checked_neg() {
+                Some(value) => Object::Integer(value),
+                None => Object::error(format!("integer overflow: -{}", value)),
+            },
+            Object::Float(value) => Object::Float(-value),
+            _ => Object::error(format!("unknown operator: -{}", right.type_name())),
+        },
+        _ => Object::error(format!(
+            "unknown operator: {}{}",
+            operator,
+            right.type_name()
+        )),
+    }
+}
+
+fn eval_infix_expression(operator: &TokenType, left: Object, right: Object) -> Object {
+    match (&left, &right) {
+        (Object::Integer(l), Object::Integer(r)) => eval_integer_infix_expression(operator, *l, *r),
+        (Object::Float(l), Object::Float(r)) => eval_float_infix_expression(operator, *l, *r),
+        // Mixed arithmetic promotes the integer side to a float.
+        (Object::Integer(l), Object::Float(r)) => {
+            eval_float_infix_expression(operator, *l as f64, *r)
+        }
+        (Object::Float(l), Object::Integer(r)) => {
+            eval_float_infix_expression(operator, *l, *r as f64)
+        }
+        (Object::String(l), Object::String(r)) => match operator {
+            TokenType::PLUS => Object::String(format!("{}{}", l, r)),
+            TokenType::EQ => Object::Boolean(l == r),
+            TokenType::NOTEQ => Object::Boolean(l != r),
+            _ => unknown_infix_operator(operator, &left, &right),
+        },
+        (Object::Array(l), Object::Array(r)) => match operator {
+            TokenType::PLUS => Object::Array(l.iter().chain(r).cloned().collect()),
+            TokenType::EQ => Object::Boolean(l == r),
+            TokenType::NOTEQ => Object::Boolean(l != r),
+            _ => unknown_infix_operator(operator, &left, &right),
+        },
+        (Object::Boolean(l), Object::Boolean(r)) => match operator {
+            TokenType::EQ => Object::Boolean(l == r),
+            TokenType::NOTEQ => Object::Boolean(l != r),
+            _ => unknown_infix_operator(operator, &left, &right),
+        },
+        _ if left.type_name() != right.type_name() => Object::error(format!(
+            "type mismatch: {} {} {}",
+            left.type_name(),
+            operator,
+            right.type_name()
+        )),
+        _ => unknown_infix_operator(operator, &left, &right),
+    }
+}
+
+fn eval_integer_infix_expression(operator: &TokenType, left: i64, right: i64) -> Object {
+    let result = match operator {
+        TokenType::PLUS => left.checked_add(right),
+        TokenType::MINUS => left.checked_sub(right),
+        TokenType::STAR => left.checked_mul(right),
+        TokenType::FSLASH | TokenType::PERCENT if right == 0 => {
+            return Object::error("division by zero")
+        }
+        TokenType::FSLASH => left.checked_div(right),
+        TokenType::PERCENT => left.checked_rem(right),
+        TokenType::LESS => return Object::Boolean(left < right),
+        TokenType::GREATER => return Object::Boolean(left > right),
+        TokenType::LESSEQ => return Object::Boolean(left <= right),
+        TokenType::GREATEREQ => return Object::Boolean(left >= right),
+        TokenType::EQ => return Object::Boolean(left == right),
+        TokenType::NOTEQ => return Object::Boolean(left != right),
+        _ => {
+            return unknown_infix_operator(
+                operator,
+                &Object::Integer(left),
+                &Object::Integer(right),
+            )
+        }
+    };
+
+    match result {
+        Some(value) => Object::Integer(value),
+        None => Object::error(format!("integer overflow: {} {} {}", left, operator, right)),
+    }
+}
+
+fn eval_float_infix_expression(operator: &TokenType, left: f64, right: f64) -> Object {
+    match operator {
+        TokenType::PLUS => Object::Float(left + right),
+        TokenType::MINUS => Object::Float(left - right),
+        TokenType::STAR => Object::Float(left * right),
+        TokenType::FSLASH => Object::Float(left / right),
+        TokenType::PERCENT => Object::Float(left % right),
+        TokenType::LESS => Object::Boolean(left < right),
+        TokenType::GREATER => Object::Boolean(left > right),
+        TokenType::LESSEQ => Object::Boolean(left <= right),
+        TokenType::GREATEREQ => Object::Boolean(left >= right),
+        TokenType::EQ => Object::Boolean(left == right),
+        TokenType::NOTEQ => Object::Boolean(left != right),
+        _ => unknown_infix_operator(operator, &Object::Float(left), &Object::Float(right)),
+    }
+}
+
+fn unknown_infix_operator(operator: &TokenType, left: &Object, right: &Object) -> Object {
+    Object::error(format!(
+        "unknown operator: {} {} {}",
+        left.type_name(),
+        operator,
+        right.type_name()
+    ))
+}
+
+/// The arithmetic operator a compound assignment applies, `+` for `+=`.
+fn compound_operator(operator: &TokenType) -> TokenType {
+    match operator {
+        TokenType::PLUSASSIGN => TokenType::PLUS,
+        TokenType::MINUSASSIGN => TokenType::MINUS,
+        TokenType::STARASSIGN => TokenType::STAR,
+        TokenType::FSLASHASSIGN => TokenType::FSLASH,
+        TokenType::PERCENTASSIGN => TokenType::PERCENT,
+        other => other.clone(),
+    }
+}
+
+fn is_truthy(object: &Object) -> bool {
+    !matches!(object, Object::Null | Object::Boolean(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, thread};
+
+    use crate::{
+        ast::ast::{ExpressionKind, Identifier, Program, Statement, StatementKind},
+        evaluator::{
+            environment::Environment,
+            object::{Key, Object},
+        },
+        lexer::Lexer,
+        parser::parser::Parser,
+        token::TokenType,
+    };
+
+    use super::{MAX_DEPTH, STACK_SIZE};
+
+    fn eval_input(input: &str) -> Object {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        super::eval_program(&program, &Environment::new())
+    }
+
+    fn eval_statements(statements: Vec<Statement>) -> Object {
+        super::eval_program(&Program { statements }, &Environment::new())
+    }
+
+    fn ident(name: &str) -> Identifier {
+        Identifier::from(name)
+    }
+
+    #[test]
+    fn integer_expressions() {
+        assert_eq!(eval_input("5;"), Object::Integer(5));
+        assert_eq!(eval_input("10; 42"), Object::Integer(42));
+    }
+
+    #[test]
+    fn prefix_expressions() {
+        let bang = |e| ExpressionKind::Prefix(TokenType::BANG, Box::new(e)).into();
+        let minus = |e| ExpressionKind::Prefix(TokenType::MINUS, Box::new(e)).into();
+
+        let tests = vec![
+            (
+                bang(ExpressionKind::IntegerLiteral(5).into()),
+                Object::Boolean(false),
+            ),
+            (
+                bang(bang(ExpressionKind::IntegerLiteral(5).into())),
+                Object::Boolean(true),
+            ),
+            (
+                minus(ExpressionKind::IntegerLiteral(5).into()),
+                Object::Integer(-5),
+            ),
+            (
+                minus(minus(ExpressionKind::IntegerLiteral(5).into())),
+                Object::Integer(5),
+            ),
+        ];
+
+        for (expression, expected) in tests {
+            let result =
+                eval_statements(vec![StatementKind::ExpressionStatement(expression).into()]);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn prefix_expressions_from_source() {
+        let tests = vec![
+            ("!5", Object::Boolean(false)),
+            ("!!5", Object::Boolean(true)),
+            ("-5", Object::Integer(-5)),
+            ("--5", Object::Integer(5)),
+            ("-5 + 10", Object::Integer(5)),
+            ("!-1 == !2", Object::Boolean(true)),
+            ("!-a", Object::error("identifier not found: a")),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn infix_expressions() {
+        let tests = vec![
+            ("5 + 5 + 5 + 5 - 10", Object::Integer(10)),
+            ("2 * 2 * 2 * 2 * 2", Object::Integer(32)),
+            ("5 + 2 * 10", Object::Integer(25)),
+            ("50 / 2 * 2 + 10", Object::Integer(60)),
+            ("1 < 2", Object::Boolean(true)),
+            ("1 > 2", Object::Boolean(false)),
+            ("1 == 1", Object::Boolean(true)),
+            ("1 != 1", Object::Boolean(false)),
+            ("1 + 2 * 3 == 7", Object::Boolean(true)),
+            ("1 < 2 == 2 < 3", Object::Boolean(true)),
+            ("(5 + 10 * 2 + 15 / 3) * 2 + -10", Object::Integer(50)),
+            ("true == true", Object::Boolean(true)),
+            ("true != false", Object::Boolean(true)),
+            ("(1 < 2) == true", Object::Boolean(true)),
+            ("(1 > 2) == true", Object::Boolean(false)),
+            ("!true", Object::Boolean(false)),
+            ("!!false", Object::Boolean(false)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn if_else_expressions() {
+        let tests = vec![
+            ("if (true) { 10 }", Object::Integer(10)),
+            ("if (false) { 10 }", Object::Null),
+            ("if (1) { 10 }", Object::Integer(10)),
+            ("if (1 < 2) { 10 }", Object::Integer(10)),
+            ("if (1 > 2) { 10 }", Object::Null),
+            ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
+            ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            (
+                "let x = 3; let y = 4; if (x < y) { x } else { y }",
+                Object::Integer(3),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn nested_return_statements() {
+        let input = "
+if (10 > 1) {
+    if (10 > 1) {
+        return 10;
+    }
+    return 1;
+}";
+
+        assert_eq!(eval_input(input), Object::Integer(10));
+    }
+
+    #[test]
+    fn function_application() {
+        let tests = vec![
+            (
+                "let identity = fn(x) { x; }; identity(5);",
+                Object::Integer(5),
+            ),
+            (
+                "let identity = fn(x) { return x; }; identity(5);",
+                Object::Integer(5),
+            ),
+            (
+                "let double = fn(x) { x * 2; }; double(5);",
+                Object::Integer(10),
+            ),
+            (
+                "let add = fn(x, y) { x + y; }; add(5, 5);",
+                Object::Integer(10),
+            ),
+            (
+                "let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));",
+                Object::Integer(20),
+            ),
+            ("fn(x) { x; }(5)", Object::Integer(5)),
+            ("fn() { return 1; 2 }() + 1", Object::Integer(2)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn closures() {
+        let input = "
+let adder = fn(x) { fn(y) { x + y } };
+let addTwo = adder(2);
+addTwo(3);";
+
+        assert_eq!(eval_input(input), Object::Integer(5));
+    }
+
+    #[test]
+    fn recursive_functions() {
+        let input = "
+let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+fib(10);";
+
+        assert_eq!(eval_input(input), Object::Integer(55));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let countdown = "let f = fn(n) { if (n == 0) { 0 } else { f(n - 1) } };";
+        // Each call nests 14 expressions deep: the call, the outer `if` and
+        // twelve more `if`s around the recursive call.
+        let nested = format!(
+            "let g = fn(n) {{ if (n == 0) {{ 0 }} else {{ {} g(n - 1) {} }} }};",
+            "if (n > 0) { ".repeat(12),
+            "} ".repeat(12)
+        );
+
+        // Test threads have a small stack, so run on one the size `main` uses.
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                assert_eq!(
+                    eval_input(&format!("{} g({})", nested, MAX_DEPTH / 14 - 1)),
+                    Object::Integer(0)
+                );
+                assert_eq!(
+                    eval_input(&format!("{} g({})", nested, MAX_DEPTH / 14 + 1)),
+                    Object::error("maximum call depth exceeded")
+                );
+                assert_eq!(
+                    eval_input(&format!("{} f(100000)", countdown)),
+                    Object::error("maximum call depth exceeded")
+                );
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn function_errors() {
+        let tests = vec![
+            (
+                "let f = fn(x) { x }; f(1, 2)",
+                "wrong number of arguments: expected 1, got 2",
+            ),
+            ("5(1)", "not a function: INTEGER"),
+            ("let f = fn(x) { x }; f(y)", "identifier not found: y"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), Object::error(expected));
+        }
+    }
+
+    #[test]
+    fn string_expressions() {
+        let tests = vec![
+            (
+                r#""Hello World!""#,
+                Object::String("Hello World!".to_string()),
+            ),
+            (
+                r#""Hello" + " " + "World!""#,
+                Object::String("Hello World!".to_string()),
+            ),
+            (r#""a\tb""#, Object::String("a\tb".to_string())),
+            (r#""a" == "a""#, Object::Boolean(true)),
+            (r#""a" != "a""#, Object::Boolean(false)),
+            (
+                r#"let greet = fn(name) { "hi " + name }; greet("bob")"#,
+                Object::String("hi bob".to_string()),
+            ),
+            (
+                r#""a" - "b""#,
+                Object::error("unknown operator: STRING - STRING"),
+            ),
+            (
+                r#""a" + 1"#,
+                Object::error("type mismatch: STRING + INTEGER"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn float_expressions() {
+        let tests = vec![
+            ("3.5", Object::Float(3.5)),
+            ("-2.5", Object::Float(-2.5)),
+            ("1.5 + 2.25", Object::Float(3.75)),
+            ("1 + 0.5", Object::Float(1.5)),
+            ("0.5 * 4", Object::Float(2.0)),
+            ("7 / 2.0", Object::Float(3.5)),
+            ("1.0 / 0", Object::Float(f64::INFINITY)),
+            ("2.5 > 2", Object::Boolean(true)),
+            ("1 == 1.0", Object::Boolean(true)),
+            ("0x10 + 0b11 + 1_000", Object::Integer(1019)),
+            (
+                "1.5 + true",
+                Object::error("type mismatch: FLOAT + BOOLEAN"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn comparison_and_remainder_operators() {
+        let tests = vec![
+            ("7 % 3", Object::Integer(1)),
+            ("-7 % 3", Object::Integer(-1)),
+            ("7.5 % 2", Object::Float(1.5)),
+            ("1 <= 1", Object::Boolean(true)),
+            ("2 <= 1", Object::Boolean(false)),
+            ("1 >= 2", Object::Boolean(false)),
+            ("2.5 >= 2", Object::Boolean(true)),
+            ("5 % 0", Object::error("division by zero")),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn logical_operators() {
+        let tests = vec![
+            ("true && true", Object::Boolean(true)),
+            ("true && false", Object::Boolean(false)),
+            ("false || true", Object::Boolean(true)),
+            ("false || false", Object::Boolean(false)),
+            ("1 < 2 && 2 < 3", Object::Boolean(true)),
+            ("0 && 1", Object::Boolean(true)),
+            // The right operand is never evaluated, so the unbound name is fine.
+            ("false && missing", Object::Boolean(false)),
+            ("true || missing", Object::Boolean(true)),
+            (
+                "true && missing",
+                Object::error("identifier not found: missing"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let tests = vec![
+            ("let x = 5; x += 2; x", Object::Integer(7)),
+            ("let x = 5; x -= 2", Object::Integer(3)),
+            ("let x = 5; x *= 2; x", Object::Integer(10)),
+            ("let x = 5; x /= 2; x", Object::Integer(2)),
+            ("let x = 5; x %= 2; x", Object::Integer(1)),
+            ("let x = 1; x += 0.5; x", Object::Float(1.5)),
+            (
+                r#"let s = "a"; s += "b"; s"#,
+                Object::String("ab".to_string()),
+            ),
+            ("let x = 1; let y = 2; x += y += 3; x", Object::Integer(6)),
+            (
+                "let count = 0; let inc = fn() { count += 1 }; inc(); inc(); count",
+                Object::Integer(2),
+            ),
+            ("x += 1", Object::error("identifier not found: x")),
+            ("let x = 1; x /= 0", Object::error("division by zero")),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn array_literals() {
+        let tests = vec![
+            (
+                "[1, 2 * 2, 3 + 3]",
+                Object::Array(vec![
+                    Object::Integer(1),
+                    Object::Integer(4),
+                    Object::Integer(6),
+                ]),
+            ),
+            ("[]", Object::Array(vec![])),
+            (
+                "[1] + [2]",
+                Object::Array(vec![Object::Integer(1), Object::Integer(2)]),
+            ),
+            ("[1, [2]] == [1, [2]]", Object::Boolean(true)),
+            (
+                "[1, missing]",
+                Object::error("identifier not found: missing"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn index_expressions() {
+        let tests = vec![
+            ("[1, 2, 3][0]", Object::Integer(1)),
+            ("[1, 2, 3][1 + 1]", Object::Integer(3)),
+            ("let i = 0; [1][i]", Object::Integer(1)),
+            ("let a = [1, 2, 3]; a[0] + a[1] + a[2]", Object::Integer(6)),
+            ("[[1, 2], [3]][0][1]", Object::Integer(2)),
+            (
+                "[1, 2, 3][3]",
+                Object::error("index out of range: 3 (length 3)"),
+            ),
+            (
+                "[1, 2, 3][-1]",
+                Object::error("index out of range: -1 (length 3)"),
+            ),
+            (
+                r#"[1][true]"#,
+                Object::error("index must be an INTEGER, got BOOLEAN"),
+            ),
+            (
+                "5[0]",
+                Object::error("index operator not supported: INTEGER"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn array_display() {
+        let result = eval_input(r#"[1, "a, b", [true], fn(x) { x }]"#);
+
+        assert_eq!(result.to_string(), r#"[1, "a, b", [true], fn(x) { ... }]"#);
+    }
+
+    #[test]
+    fn hash_literals() {
+        let input = r#"
+let two = "two";
+{
+    "one": 10 - 9,
+    two: 1 + 1,
+    "thr" + "ee": 6 / 2,
+    4: 4,
+    true: 5,
+    false: 6
+}"#;
+
+        let expected = BTreeMap::from([
+            (Key::String("one".to_string()), Object::Integer(1)),
+            (Key::String("two".to_string()), Object::Integer(2)),
+            (Key::String("three".to_string()), Object::Integer(3)),
+            (Key::Integer(4), Object::Integer(4)),
+            (Key::Boolean(true), Object::Integer(5)),
+            (Key::Boolean(false), Object::Integer(6)),
+        ]);
+
+        assert_eq!(eval_input(input), Object::Hash(expected));
+    }
+
+    #[test]
+    fn hash_index_expressions() {
+        let tests = vec![
+            (r#"{"foo": 5}["foo"]"#, Object::Integer(5)),
+            (r#"{"foo": 5}["bar"]"#, Object::Null),
+            (r#"let key = "foo"; {"foo": 5}[key]"#, Object::Integer(5)),
+            (r#"{}["foo"]"#, Object::Null),
+            ("{5: 5}[5]", Object::Integer(5)),
+            ("{true: 5}[true]", Object::Integer(5)),
+            ("{1: 1, 1: 2}[1]", Object::Integer(2)),
+            (
+                r#"let m = {"name": "x", 1: true}; m["name"]"#,
+                Object::String("x".to_string()),
+            ),
+            (
+                r#"{"name": "x"}[fn(x) { x }]"#,
+                Object::error("unusable as hash key: FUNCTION"),
+            ),
+            ("{[1]: 2}", Object::error("unusable as hash key: ARRAY")),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn hash_display() {
+        let result = eval_input(r#"{"b": [1, "x"], 2: true}"#);
+        assert_eq!(result.to_string(), r#"{2: true, "b": [1, "x"]}"#);
+    }
+
+    #[test]
+    fn builtin_functions() {
+        let tests = vec![
+            (r#"len("")"#, Object::Integer(0)),
+            (r#"len("four")"#, Object::Integer(4)),
+            (r#"len("héllo")"#, Object::Integer(5)),
+            ("len([1, 2, 3])", Object::Integer(3)),
+            (r#"len({"a": 1})"#, Object::Integer(1)),
+            ("first([1, 2, 3])", Object::Integer(1)),
+            ("first([])", Object::Null),
+            ("last([1, 2, 3])", Object::Integer(3)),
+            ("last([])", Object::Null),
+            (
+                "rest([1, 2, 3])",
+                Object::Array(vec![Object::Integer(2), Object::Integer(3)]),
+            ),
+            ("rest([1])", Object::Array(vec![])),
+            ("rest([])", Object::Null),
+            (
+                "let a = [1]; push(a, 2)",
+                Object::Array(vec![Object::Integer(1), Object::Integer(2)]),
+            ),
+            (
+                "let a = [1]; push(a, 2); a",
+                Object::Array(vec![Object::Integer(1)]),
+            ),
+            ("type(1.5)", Object::String("FLOAT".to_string())),
+            ("type(len)", Object::String("BUILTIN".to_string())),
+            ("puts()", Object::Null),
+            // User bindings shadow builtins.
+            ("let len = fn(x) { 42 }; len([])", Object::Integer(42)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn builtin_errors() {
+        let tests = vec![
+            ("len(1)", "argument to `len` not supported, got INTEGER"),
+            (
+                r#"len("one", "two")"#,
+                "wrong number of arguments: expected 1, got 2",
+            ),
+            ("first(1)", "argument to `first` not supported, got INTEGER"),
+            ("rest()", "wrong number of arguments: expected 1, got 0"),
+            (
+                "push(1, 1)",
+                "argument to `push` not supported, got INTEGER",
+            ),
+            ("push([])", "wrong number of arguments: expected 2, got 1"),
+            ("type()", "wrong number of arguments: expected 1, got 0"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), Object::error(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn runtime_error_spans() {
+        let tests = vec![
+            ("1 + foo", "foo"),
+            ("let x = 1;\nx + true", "x + true"),
+            ("let f = fn(a) { a / 0 }; f(1)", "a / 0"),
+            ("let x = 1; y += 1", "y"),
+            ("len(1, 2)", "len(1, 2)"),
+        ];
+
+        for (input, expected) in tests {
+            match eval_input(input) {
+                Object::Error(error) => {
+                    assert_eq!(
+                        &input[error.span.start..error.span.end],
+                        expected,
+                        "{}",
+                        input
+                    )
+                }
+                other => panic!("expected an error for {}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn let_statements() {
+        let result = eval_statements(vec![
+            StatementKind::LetStatement(ident("a"), ExpressionKind::IntegerLiteral(5).into())
+                .into(),
+            StatementKind::LetStatement(ident("b"), ExpressionKind::Identifier(ident("a")).into())
+                .into(),
+            StatementKind::ExpressionStatement(ExpressionKind::Identifier(ident("b")).into())
+                .into(),
+        ]);
+
+        assert_eq!(result, Object::Integer(5));
+    }
+
+    #[test]
+    fn let_statements_from_source() {
+        let tests = vec![
+            ("let a = 5; a;", Object::Integer(5)),
+            ("let a = 5 * 5; a;", Object::Integer(25)),
+            ("let a = 5; let b = a; b;", Object::Integer(5)),
+            (
+                "let a = 5; let b = a; let c = a + b + 5; c",
+                Object::Integer(15),
+            ),
+            ("return 2 * 5; 9;", Object::Integer(10)),
+            ("9; return 10", Object::Integer(10)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn return_statements() {
+        let result = eval_statements(vec![
+            StatementKind::ExpressionStatement(ExpressionKind::IntegerLiteral(9).into()).into(),
+            StatementKind::ReturnStatement(ExpressionKind::IntegerLiteral(10).into()).into(),
+            StatementKind::ExpressionStatement(ExpressionKind::IntegerLiteral(11).into()).into(),
+        ]);
+
+        assert_eq!(result, Object::Integer(10));
+    }
+
+    #[test]
+    fn error_handling() {
+        assert_eq!(
+            eval_input("foobar"),
+            Object::error("identifier not found: foobar")
+        );
+
+        let result = eval_statements(vec![
+            StatementKind::ExpressionStatement(
+                ExpressionKind::Prefix(
+                    TokenType::MINUS,
+                    Box::new(
+                        ExpressionKind::Prefix(
+                            TokenType::BANG,
+                            Box::new(ExpressionKind::IntegerLiteral(1).into()),
+                        )
+                        .into(),
+                    ),
+                )
+                .into(),
+            )
+            .into(),
+            StatementKind::ExpressionStatement(ExpressionKind::IntegerLiteral(5).into()).into(),
+        ]);
+        assert_eq!(result, Object::error("unknown operator: -BOOLEAN"));
+
+        let tests = vec![
+            ("5 / 0", "division by zero"),
+            (
+                "9223372036854775807 + 1",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            ("1 < 2 + 3 < 4", "type mismatch: BOOLEAN < INTEGER"),
+            ("1 < 2 + 3 == 4", "type mismatch: BOOLEAN == INTEGER"),
+            ("-!5", "unknown operator: -BOOLEAN"),
+            ("!1 + !2", "unknown operator: BOOLEAN + BOOLEAN"),
+            ("5 + true; 5;", "type mismatch: INTEGER + BOOLEAN"),
+            ("true * false", "unknown operator: BOOLEAN * BOOLEAN"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(eval_input(input), Object::error(expected));
+        }
+    }
+}
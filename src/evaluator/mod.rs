@@ -0,0 +1,4 @@
+pub mod builtins;
+pub mod environment;
+pub mod evaluator;
+pub mod object;
@@ -0,0 +1,193 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
+
+use crate::{
+    ast::ast::{BlockStatement, Identifier},
+    diagnostic::{Diagnostic, RUNTIME_ERROR},
+    token::Span,
+};
+
+use super::environment::Environment;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Object {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Array(Vec<Object>),
+    Hash(BTreeMap<Key, Object>),
+    Null,
+    ReturnValue(Box<Object>),
+    Error(RuntimeError),
+    Function(Function),
+    Builtin(&'static Builtin),
+}
+
+/// An error raised during evaluation, located at the innermost expression
+/// that failed. Compared by message only, like AST nodes.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// A native function from the [`builtins`](super::builtins) table.
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub function: fn(Vec<Object>) -> Object,
+}
+
+/// The identity of a hashable object, used to store it in an [`Object::Hash`].
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum Key {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+/// Objects that can be used as hash keys.
+pub trait HashKey {
+    /// Returns `None` for objects that cannot be hashed, such as arrays.
+    fn hash_key(&self) -> Option<Key>;
+}
+
+#[derive(Clone)]
+pub struct Function {
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+    pub env: Rc<RefCell<Environment>>,
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Null => "NULL",
+            Object::ReturnValue(_) => "RETURN_VALUE",
+            Object::Error(_) => "ERROR",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+        }
+    }
+
+    /// An error without a location yet; the evaluator fills in the span of
+    /// the expression being evaluated.
+    pub fn error(message: impl Into<String>) -> Object {
+        Object::Error(RuntimeError {
+            message: message.into(),
+            span: Span::default(),
+        })
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Object::Error(_))
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(inspect).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, inspect(value)))
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Null => write!(f, "null"),
+            Object::ReturnValue(value) => write!(f, "{}", value),
+            Object::Error(error) => write!(f, "ERROR: {}", error.message),
+            Object::Function(function) => {
+                let params: Vec<&str> = function
+                    .parameters
+                    .iter()
+                    .map(|Identifier { name, .. }| name.as_str())
+                    .collect();
+                write!(f, "fn({}) {{ ... }}", params.join(", "))
+            }
+            Object::Builtin(builtin) => write!(f, "builtin function {}", builtin.name),
+        }
+    }
+}
+
+impl HashKey for Object {
+    fn hash_key(&self) -> Option<Key> {
+        match self {
+            Object::Integer(value) => Some(Key::Integer(*value)),
+            Object::Boolean(value) => Some(Key::Boolean(*value)),
+            Object::String(value) => Some(Key::String(value.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Integer(value) => write!(f, "{}", value),
+            Key::Boolean(value) => write!(f, "{}", value),
+            Key::String(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// Formats an object nested inside an array or hash, quoting strings so that
+/// `["a, b"]` and `["a", "b"]` print differently.
+fn inspect(object: &Object) -> String {
+    match object {
+        Object::String(value) => format!("{:?}", value),
+        other => other.to_string(),
+    }
+}
+
+// The captured environment usually contains the function itself, so it is left
+// out of `Debug` and compared by identity to avoid infinite recursion.
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("parameters", &self.parameters)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+impl PartialEq for RuntimeError {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+    }
+}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(error: RuntimeError) -> Diagnostic {
+        Diagnostic::error(RUNTIME_ERROR, error.message, error.span)
+    }
+}
+
+// Builtins are unique per name, and comparing function pointers is unreliable.
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters == other.parameters
+            && self.body == other.body
+            && Rc::ptr_eq(&self.env, &other.env)
+    }
+}
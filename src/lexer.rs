@@ -1,202 +1,567 @@
-use crate::token::{Token, TokenType};
+use std::{fmt, iter::Peekable, str::CharIndices};
 
-pub struct Lexer {
-    pub input: String,
-    pub position: usize,
-    pub read_position: usize,
+use crate::{
+    diagnostic::{
+        Diagnostic, INVALID_CHARACTER, INVALID_ESCAPE, INVALID_LITERAL, UNTERMINATED_COMMENT,
+        UNTERMINATED_STRING,
+    },
+    token::{Span, Token, TokenType},
+};
+
+pub struct Lexer<'a> {
+    pub input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
     pub ch: Option<char>,
+    /// Byte offset, line and column of `ch`.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    /// Set once the iterator has yielded `EOF`.
+    done: bool,
+    /// One entry for every `ILLEGAL` token, explaining what is wrong with it.
+    pub errors: Vec<LexError>,
+    /// Yield `///` comments as `DOCCOMMENT` tokens instead of skipping them,
+    /// for tools such as formatters and doc generators.
+    pub keep_doc_comments: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub code: &'static str,
+    pub message: String,
+    pub label: &'static str,
+    pub span: Span,
+}
+
+/// An invalid escape sequence, located by byte offsets into the string body.
+#[derive(Debug, PartialEq)]
+pub struct EscapeError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
 }
 
-impl Lexer {
-    pub fn new(input: &str) -> Lexer {
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Lexer<'a> {
         let mut l = Lexer {
-            input: input.to_string(),
-            position: 0,
-            read_position: 0,
+            input,
+            chars: input.char_indices().peekable(),
             ch: None,
+            offset: 0,
+            line: 1,
+            // Reading the first character moves onto column 1.
+            column: 0,
+            done: false,
+            errors: Vec::new(),
+            keep_doc_comments: false,
         };
         l.read_char();
-        return l;
+        l
     }
 
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = None;
-        } else {
-            self.ch = Some(self.input.chars().nth(self.read_position).unwrap());
+        match self.ch {
+            Some('\n') => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => self.column += 1,
+        }
+
+        match self.chars.next() {
+            Some((offset, ch)) => {
+                self.offset = offset;
+                self.ch = Some(ch);
+            }
+            None => {
+                self.offset = self.input.len();
+                self.ch = None;
+            }
         }
-        self.position = self.read_position;
-        self.read_position += 1;
     }
 
-    fn peek_char(&self) -> Option<char> {
-        if self.read_position >= self.input.len() {
-            return None;
-        } else {
-            return Some(self.input.chars().nth(self.read_position).unwrap());
+    fn peek_char(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, ch)| ch)
+    }
+
+    pub fn next_token(&mut self) -> Token<'a> {
+        loop {
+            let token = self.read_trivia_or_token();
+            match token.token_type {
+                TokenType::COMMENT => continue,
+                TokenType::DOCCOMMENT if !self.keep_doc_comments => continue,
+                _ => return token,
+            }
         }
     }
 
-    pub fn next_token(&mut self) -> Token {
-        let token: Token;
+    fn read_trivia_or_token(&mut self) -> Token<'a> {
         self.skip_whitespace();
 
-        match self.ch {
-            None => token = new_token(TokenType::EOF, "".to_string()),
+        let (start, line, column) = (self.offset, self.line, self.column);
+        let errors = self.errors.len();
+        let token_type = self.read_token();
+        let token = Token {
+            token_type,
+            literal: &self.input[start..self.offset],
+            span: Span {
+                start,
+                end: self.offset,
+                line,
+                column,
+            },
+        };
+
+        if token.token_type == TokenType::ILLEGAL && self.errors.len() == errors {
+            self.errors.push(LexError {
+                code: INVALID_CHARACTER,
+                message: format!("unexpected character `{}`", token.literal),
+                label: "not valid here",
+                span: token.span,
+            });
+        }
+        token
+    }
+
+    fn read_token(&mut self) -> TokenType {
+        let token_type = match self.ch {
+            None => return TokenType::EOF,
             Some(ch) => match ch {
-                '=' => {
-                    if self.peek_char() == Some('=') {
-                        self.read_char();
-                        token = new_token(TokenType::EQ, "==".to_string());
-                    } else {
-                        token = new_token(TokenType::ASSIGN, ch.to_string());
-                    }
-                },
-                ';' => token = new_token(TokenType::SEMICOLON, ch.to_string()),
-                '(' => token = new_token(TokenType::LPAREN, ch.to_string()),
-                ')' => token = new_token(TokenType::RPAREN, ch.to_string()),
-                '{' => token = new_token(TokenType::LBRACE, ch.to_string()),
-                '}' => token = new_token(TokenType::RBRACE, ch.to_string()),
-                '+' => token = new_token(TokenType::PLUS, ch.to_string()),
-                '-' => token = new_token(TokenType::MINUS, ch.to_string()),
-                ',' => token = new_token(TokenType::COMMA, ch.to_string()),
-                '*' => token = new_token(TokenType::STAR, ch.to_string()),
-                '/' => token = new_token(TokenType::FSLASH, ch.to_string()),
-                '!' => {
-                    if self.peek_char() == Some('=') {
-                        self.read_char();
-                        token = new_token(TokenType::NOTEQ, "!=".to_string());
-                    } else {
-                        token = new_token(TokenType::BANG, ch.to_string());
-                    }
+                '=' => self.either('=', TokenType::EQ, TokenType::ASSIGN),
+                ';' => TokenType::SEMICOLON,
+                '(' => TokenType::LPAREN,
+                ')' => TokenType::RPAREN,
+                '{' => TokenType::LBRACE,
+                '}' => TokenType::RBRACE,
+                '[' => TokenType::LBRACKET,
+                ']' => TokenType::RBRACKET,
+                '+' => self.either('=', TokenType::PLUSASSIGN, TokenType::PLUS),
+                '-' => self.either('=', TokenType::MINUSASSIGN, TokenType::MINUS),
+                ',' => TokenType::COMMA,
+                ':' => TokenType::COLON,
+                '*' => self.either('=', TokenType::STARASSIGN, TokenType::STAR),
+                '/' => match self.peek_char() {
+                    Some('/') | Some('*') => return self.read_comment(),
+                    _ => self.either('=', TokenType::FSLASHASSIGN, TokenType::FSLASH),
                 },
-                '<' => token = new_token(TokenType::LESS, ch.to_string()),
-                '>' => token = new_token(TokenType::GREATER, ch.to_string()),
+                '%' => self.either('=', TokenType::PERCENTASSIGN, TokenType::PERCENT),
+                '!' => self.either('=', TokenType::NOTEQ, TokenType::BANG),
+                '<' => self.either('=', TokenType::LESSEQ, TokenType::LESS),
+                '>' => self.either('=', TokenType::GREATEREQ, TokenType::GREATER),
+                '&' => self.either('&', TokenType::AND, TokenType::ILLEGAL),
+                '|' => self.either('|', TokenType::OR, TokenType::ILLEGAL),
+                '"' => self.read_string(),
                 ch => {
-                    if ch.is_alphabetic() {
-                        let literal = self.read_identifier();
-                        token = new_token(is_identifier(literal.as_str()), literal);
-                        return token;
-                    } else if ch.is_digit(10){
-                        let literal = self.read_number();
-                        token = new_token(TokenType::INT, literal);
-                        return token;
+                    if is_identifier_start(ch) {
+                        return is_identifier(self.read_identifier());
+                    } else if ch.is_ascii_digit() {
+                        return self.read_number();
                     } else {
-                        token = new_token(TokenType::ILLEGAL, ch.to_string())
+                        TokenType::ILLEGAL
                     }
                 }
             },
         };
         self.read_char();
-        return token;
+        token_type
+    }
+
+    /// Reads a `//` line comment or a `/* */` block comment, which may nest.
+    /// Line comments starting with exactly three slashes are doc comments.
+    fn read_comment(&mut self) -> TokenType {
+        let (start, line, column) = (self.offset, self.line, self.column);
+        self.read_char();
+
+        if self.ch == Some('/') {
+            self.read_char();
+            let doc = self.ch == Some('/') && self.peek_char() != Some('/');
+            self.read_while(|ch| ch != '\n');
+            return if doc {
+                TokenType::DOCCOMMENT
+            } else {
+                TokenType::COMMENT
+            };
+        }
+
+        self.read_char();
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.ch, self.peek_char()) {
+                (None, _) => {
+                    self.errors.push(LexError {
+                        code: UNTERMINATED_COMMENT,
+                        message: "unterminated block comment".to_string(),
+                        label: "comment starts here",
+                        span: Span {
+                            start,
+                            end: start + 2,
+                            line,
+                            column,
+                        },
+                    });
+                    return TokenType::ILLEGAL;
+                }
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.read_char();
+                    self.read_char();
+                }
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.read_char();
+                    self.read_char();
+                }
+                _ => self.read_char(),
+            }
+        }
+
+        TokenType::COMMENT
+    }
+
+    /// Reads a string literal up to and including its closing quote. Escape
+    /// sequences are only validated here; the parser decodes them with
+    /// [`unescape`].
+    fn read_string(&mut self) -> TokenType {
+        let (start, line, column) = (self.offset, self.line, self.column);
+        self.read_char();
+
+        while self.ch != Some('"') {
+            match self.ch {
+                None => {
+                    self.errors.push(LexError {
+                        code: UNTERMINATED_STRING,
+                        message: "unterminated string literal".to_string(),
+                        label: "string starts here",
+                        span: Span {
+                            start,
+                            end: start + 1,
+                            line,
+                            column,
+                        },
+                    });
+                    return TokenType::ILLEGAL;
+                }
+                Some('\\') => {
+                    self.read_char();
+                    if self.ch.is_some() {
+                        self.read_char();
+                    }
+                }
+                Some(_) => self.read_char(),
+            }
+        }
+
+        let body = &self.input[start + 1..self.offset];
+        if let Err(error) = unescape(body) {
+            let (line, column) = advance(line, column + 1, &body[..error.start]);
+            self.errors.push(LexError {
+                code: INVALID_ESCAPE,
+                message: error.message,
+                label: "invalid escape",
+                span: Span {
+                    start: start + 1 + error.start,
+                    end: start + 1 + error.end,
+                    line,
+                    column,
+                },
+            });
+            self.read_char();
+            return TokenType::ILLEGAL;
+        }
+
+        TokenType::STRING
+    }
+
+    /// Consumes `next` and returns `matched` if it follows the current
+    /// character, otherwise returns `otherwise`.
+    fn either(&mut self, next: char, matched: TokenType, otherwise: TokenType) -> TokenType {
+        if self.peek_char() == Some(next) {
+            self.read_char();
+            matched
+        } else {
+            otherwise
+        }
     }
 
-    fn read_number(&mut self) -> String {
-        let mut result = String::new();
+    /// Reads an `INT` or `FLOAT` literal. Integers may start with a `0x`,
+    /// `0b` or `0o` radix prefix, and digits may be separated by `_`.
+    fn read_number(&mut self) -> TokenType {
+        let radix = match (self.ch, self.peek_char()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('b' | 'B')) => 2,
+            (Some('0'), Some('o' | 'O')) => 8,
+            _ => 10,
+        };
 
-        while self.ch.is_some() && self.ch.unwrap().is_digit(10) {
-            result.push(self.ch.unwrap());
+        if radix != 10 {
+            let (start, line, column) = (self.offset, self.line, self.column);
+            self.read_char();
             self.read_char();
+            let digits = self.read_while(|ch| ch.is_digit(radix) || ch == '_');
+            if digits.chars().all(|ch| ch == '_') {
+                self.errors.push(LexError {
+                    code: INVALID_LITERAL,
+                    message: format!("missing digits after `{}`", &self.input[start..start + 2]),
+                    label: "expected digits here",
+                    span: Span {
+                        start,
+                        end: self.offset,
+                        line,
+                        column,
+                    },
+                });
+                return TokenType::ILLEGAL;
+            }
+            return TokenType::INT;
         }
-        return result;
+
+        let mut token_type = TokenType::INT;
+        self.read_while(is_decimal_char);
+
+        if self.ch == Some('.') && self.peek_char().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.read_char();
+            self.read_while(is_decimal_char);
+            token_type = TokenType::FLOAT;
+        }
+
+        if matches!(self.ch, Some('e' | 'E')) {
+            // Only an exponent if digits follow, so `2else` still lexes as `2 else`.
+            let mut ahead = self.chars.clone().map(|(_, ch)| ch);
+            let digit = match ahead.next() {
+                Some('+' | '-') => ahead.next(),
+                next => next,
+            };
+            if digit.is_some_and(|ch| ch.is_ascii_digit()) {
+                self.read_char();
+                if matches!(self.ch, Some('+' | '-')) {
+                    self.read_char();
+                }
+                self.read_while(is_decimal_char);
+                token_type = TokenType::FLOAT;
+            }
+        }
+
+        token_type
     }
 
-    fn read_identifier(&mut self) -> String {
-        let mut result = String::new();
+    fn read_identifier(&mut self) -> &'a str {
+        self.read_while(is_identifier_char)
+    }
 
-        while self.ch.is_some() && self.ch.unwrap().is_alphabetic() {
-            result.push(self.ch.unwrap());
+    /// Consumes characters while `predicate` holds and returns them as a slice
+    /// of the input.
+    fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        while self.ch.is_some_and(&predicate) {
             self.read_char();
         }
-        return result;
+        &self.input[start..self.offset]
     }
 
     fn skip_whitespace(&mut self) {
-        while self.ch.is_some() && self.ch.unwrap().is_whitespace() {
+        while self.ch.is_some_and(char::is_whitespace) {
             self.read_char();
         }
     }
 }
 
-pub fn new_token(token_type: TokenType, literal: String) -> Token {
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    /// Yields every token up to and including `EOF`, then `None`.
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.done {
+            return None;
+        }
+        let token = self.next_token();
+        self.done = token.token_type == TokenType::EOF;
+        Some(token)
+    }
+}
+
+/// Lexes the whole input, failing on the first token that is not valid. The
+/// returned tokens end with `EOF`.
+// For tools built on the lexer; the binary itself streams tokens instead.
+#[allow(dead_code)]
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, LexError> {
+    let mut lexer = Lexer::new(input);
+    let tokens: Vec<Token> = lexer.by_ref().collect();
+
+    match lexer.errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(tokens),
+    }
+}
+
+/// Decodes the escape sequences in the body of a string literal: `\n`, `\t`,
+/// `\"`, `\\` and `\u{...}` with one to six hex digits.
+pub fn unescape(body: &str) -> Result<String, EscapeError> {
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.char_indices();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        let escaped = chars.next();
+        let end = chars.offset();
+        let decoded = match escaped {
+            Some((_, 'n')) => Some('\n'),
+            Some((_, 't')) => Some('\t'),
+            Some((_, '"')) => Some('"'),
+            Some((_, '\\')) => Some('\\'),
+            Some((_, 'u')) => {
+                let digits = body[end..]
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(digits, _)| digits)
+                    .filter(|digits| {
+                        (1..=6).contains(&digits.len())
+                            && digits.chars().all(|ch| ch.is_ascii_hexdigit())
+                    });
+                let Some(digits) = digits else {
+                    return Err(EscapeError {
+                        message: "expected `\\u{...}` with one to six hex digits".to_string(),
+                        start,
+                        end,
+                    });
+                };
+
+                // The braces and digits are all ASCII, one byte per char.
+                chars.nth(digits.len() + 1);
+                let end = end + digits.len() + 2;
+                let value = u32::from_str_radix(digits, 16).expect("validated hex digits");
+                match char::from_u32(value) {
+                    Some(ch) => Some(ch),
+                    None => {
+                        return Err(EscapeError {
+                            message: format!("`{}` is not a unicode character", &body[start..end]),
+                            start,
+                            end,
+                        })
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        match decoded {
+            Some(ch) => result.push(ch),
+            None => {
+                return Err(EscapeError {
+                    message: format!("unknown escape sequence `{}`", &body[start..end]),
+                    start,
+                    end,
+                })
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Moves a line and column position past `text`.
+fn advance(mut line: usize, mut column: usize, text: &str) -> (usize, usize) {
+    for ch in text.chars() {
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(error: LexError) -> Diagnostic {
+        Diagnostic::error(error.code, error.message, error.span).with_label(error.span, error.label)
+    }
+}
+
+pub fn new_token(token_type: TokenType, literal: &str) -> Token<'_> {
     Token {
         token_type,
         literal,
+        span: Span::default(),
     }
 }
 
+fn is_decimal_char(ch: char) -> bool {
+    ch.is_ascii_digit() || ch == '_'
+}
+
+fn is_identifier_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_'
+}
+
+pub fn is_identifier_char(ch: char) -> bool {
+    is_identifier_start(ch) || ch.is_ascii_digit()
+}
+
+/// Reserved words and the tokens they lex to.
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("let", TokenType::LET),
+    ("fn", TokenType::FUNCTION),
+    ("if", TokenType::IF),
+    ("else", TokenType::ELSE),
+    ("return", TokenType::RETURN),
+    ("true", TokenType::TRUE),
+    ("false", TokenType::FALSE),
+];
+
 fn is_identifier(token: &str) -> TokenType {
-    match token {
-        "let" => TokenType::LET,
-        "fn" => TokenType::FUNCTION,
-        "if" => TokenType::IF,
-        "else" => TokenType::ELSE,
-        "return" => TokenType::RETURN,
-        "true" => TokenType::TRUE,
-        "false" => TokenType::FALSE,
-        _ => TokenType::IDENT,
-    }
+    KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == token)
+        .map_or(TokenType::IDENT, |(_, token_type)| token_type.clone())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::token;
+    use crate::token::{Span, TokenType};
 
-    use super::Lexer;
+    use crate::diagnostic::{
+        INVALID_ESCAPE, INVALID_LITERAL, UNTERMINATED_COMMENT, UNTERMINATED_STRING,
+    };
+
+    use super::{tokenize, unescape, Lexer};
 
     #[test]
     fn lexer_tokenizer_simple() {
         let input = "=+(){},;";
 
         let expected = vec![
-            token::Token {
-                token_type: token::TokenType::ASSIGN,
-                literal: "=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::PLUS,
-                literal: "+".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LPAREN,
-                literal: "(".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RPAREN,
-                literal: ")".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LBRACE,
-                literal: "{".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RBRACE,
-                literal: "}".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::COMMA,
-                literal: ",".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::EOF,
-                literal: "".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::EOF,
-                literal: "".to_string(),
-            },
+            (TokenType::ASSIGN, "="),
+            (TokenType::PLUS, "+"),
+            (TokenType::LPAREN, "("),
+            (TokenType::RPAREN, ")"),
+            (TokenType::LBRACE, "{"),
+            (TokenType::RBRACE, "}"),
+            (TokenType::COMMA, ","),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::EOF, ""),
+            (TokenType::EOF, ""),
         ];
 
         let mut lexer = Lexer::new(input);
 
-        for i in 0..expected.len() {
+        for (token_type, literal) in expected {
             let tok = lexer.next_token();
-            assert_eq!(tok.token_type, expected[i].token_type);
-            assert_eq!(tok.literal, expected[i].literal);
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
         }
     }
 
@@ -208,7 +573,7 @@ let ten = 10;
      x + y;
 };
    let result = add(five, ten);
-   !-/*5;
+   !-/ *5;
    5 < 10 > 5;
    if (5 < 10) {
        return true;
@@ -220,310 +585,467 @@ let ten = 10;
 ";
 
         let expected = vec![
-            token::Token {
-                token_type: token::TokenType::LET,
-                literal: "let".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "five".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::ASSIGN,
-                literal: "=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "5".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LET,
-                literal: "let".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "ten".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::ASSIGN,
-                literal: "=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LET,
-                literal: "let".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "add".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::ASSIGN,
-                literal: "=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::FUNCTION,
-                literal: "fn".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LPAREN,
-                literal: "(".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "x".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::COMMA,
-                literal: ",".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "y".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RPAREN,
-                literal: ")".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LBRACE,
-                literal: "{".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "x".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::PLUS,
-                literal: "+".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "y".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RBRACE,
-                literal: "}".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LET,
-                literal: "let".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "result".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::ASSIGN,
-                literal: "=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "add".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LPAREN,
-                literal: "(".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "five".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::COMMA,
-                literal: ",".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IDENT,
-                literal: "ten".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RPAREN,
-                literal: ")".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::BANG,
-                literal: "!".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::MINUS,
-                literal: "-".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::FSLASH,
-                literal: "/".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::STAR,
-                literal: "*".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "5".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "5".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LESS,
-                literal: "<".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::GREATER,
-                literal: ">".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "5".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::IF,
-                literal: "if".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LPAREN,
-                literal: "(".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "5".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LESS,
-                literal: "<".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RPAREN,
-                literal: ")".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LBRACE,
-                literal: "{".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RETURN,
-                literal: "return".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::TRUE,
-                literal: "true".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RBRACE,
-                literal: "}".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::ELSE,
-                literal: "else".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::LBRACE,
-                literal: "{".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RETURN,
-                literal: "return".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::FALSE,
-                literal: "false".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::RBRACE,
-                literal: "}".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::EQ,
-                literal: "==".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "10".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::NOTEQ,
-                literal: "!=".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::INT,
-                literal: "9".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::SEMICOLON,
-                literal: ";".to_string(),
-            },
-            token::Token {
-                token_type: token::TokenType::EOF,
-                literal: "".to_string(),
-            },
+            (TokenType::LET, "let"),
+            (TokenType::IDENT, "five"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::INT, "5"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::LET, "let"),
+            (TokenType::IDENT, "ten"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::INT, "10"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::LET, "let"),
+            (TokenType::IDENT, "add"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::FUNCTION, "fn"),
+            (TokenType::LPAREN, "("),
+            (TokenType::IDENT, "x"),
+            (TokenType::COMMA, ","),
+            (TokenType::IDENT, "y"),
+            (TokenType::RPAREN, ")"),
+            (TokenType::LBRACE, "{"),
+            (TokenType::IDENT, "x"),
+            (TokenType::PLUS, "+"),
+            (TokenType::IDENT, "y"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::RBRACE, "}"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::LET, "let"),
+            (TokenType::IDENT, "result"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::IDENT, "add"),
+            (TokenType::LPAREN, "("),
+            (TokenType::IDENT, "five"),
+            (TokenType::COMMA, ","),
+            (TokenType::IDENT, "ten"),
+            (TokenType::RPAREN, ")"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::BANG, "!"),
+            (TokenType::MINUS, "-"),
+            (TokenType::FSLASH, "/"),
+            (TokenType::STAR, "*"),
+            (TokenType::INT, "5"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::INT, "5"),
+            (TokenType::LESS, "<"),
+            (TokenType::INT, "10"),
+            (TokenType::GREATER, ">"),
+            (TokenType::INT, "5"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IF, "if"),
+            (TokenType::LPAREN, "("),
+            (TokenType::INT, "5"),
+            (TokenType::LESS, "<"),
+            (TokenType::INT, "10"),
+            (TokenType::RPAREN, ")"),
+            (TokenType::LBRACE, "{"),
+            (TokenType::RETURN, "return"),
+            (TokenType::TRUE, "true"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::RBRACE, "}"),
+            (TokenType::ELSE, "else"),
+            (TokenType::LBRACE, "{"),
+            (TokenType::RETURN, "return"),
+            (TokenType::FALSE, "false"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::RBRACE, "}"),
+            (TokenType::INT, "10"),
+            (TokenType::EQ, "=="),
+            (TokenType::INT, "10"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::INT, "10"),
+            (TokenType::NOTEQ, "!="),
+            (TokenType::INT, "9"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn token_spans() {
+        let input = "let x = 10;\n  x == abc;";
+
+        let expected = vec![
+            (0, 3, 1, 1),
+            (4, 5, 1, 5),
+            (6, 7, 1, 7),
+            (8, 10, 1, 9),
+            (10, 11, 1, 11),
+            (14, 15, 2, 3),
+            (16, 18, 2, 5),
+            (19, 22, 2, 8),
+            (22, 23, 2, 11),
+            (23, 23, 2, 12),
         ];
 
         let mut lexer = Lexer::new(input);
 
-        for i in 0..expected.len() {
+        for (start, end, line, column) in expected {
             let tok = lexer.next_token();
-            assert_eq!(tok.token_type, expected[i].token_type);
-            assert_eq!(tok.literal, expected[i].literal);
+            let span = Span {
+                start,
+                end,
+                line,
+                column,
+            };
+            assert_eq!(tok.span, span, "{:?}", tok);
+        }
+    }
+
+    #[test]
+    fn non_ascii_input() {
+        let input = "let café = «5»;\nnaïve";
+
+        let expected = vec![
+            (TokenType::LET, "let", 1, 1),
+            (TokenType::IDENT, "café", 1, 5),
+            (TokenType::ASSIGN, "=", 1, 10),
+            (TokenType::ILLEGAL, "«", 1, 12),
+            (TokenType::INT, "5", 1, 13),
+            (TokenType::ILLEGAL, "»", 1, 14),
+            (TokenType::SEMICOLON, ";", 1, 15),
+            (TokenType::IDENT, "naïve", 2, 1),
+            (TokenType::EOF, "", 2, 6),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal, line, column) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+            assert_eq!((tok.span.line, tok.span.column), (line, column));
+            assert_eq!(&input[tok.span.start..tok.span.end], literal);
+        }
+    }
+
+    #[test]
+    fn large_input() {
+        let input = "let x = 10;\n".repeat(200_000);
+
+        let mut lexer = Lexer::new(&input);
+        let mut count = 0;
+        while lexer.next_token().token_type != TokenType::EOF {
+            count += 1;
         }
+
+        assert_eq!(count, 1_000_000);
+    }
+
+    #[test]
+    fn iterator_ends_after_eof() {
+        let tokens: Vec<TokenType> = Lexer::new("x + 1").map(|tok| tok.token_type).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::IDENT,
+                TokenType::PLUS,
+                TokenType::INT,
+                TokenType::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn iterator_adapters() {
+        let mut tokens = Lexer::new("let a = 1; let b = 2;").peekable();
+
+        assert_eq!(tokens.peek().map(|tok| tok.literal), Some("let"));
+        let idents: Vec<&str> = tokens
+            .filter(|tok| tok.token_type == TokenType::IDENT)
+            .map(|tok| tok.literal)
+            .collect();
+        assert_eq!(idents, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn tokenize_input() {
+        let tokens = tokenize("fn(x) { x }").unwrap();
+        assert_eq!(tokens.len(), 8);
+        assert_eq!(tokens.last().unwrap().token_type, TokenType::EOF);
+
+        let error = tokenize("let x = 1;\nlet y = @;").unwrap_err();
+        assert_eq!(error.message, "unexpected character `@`");
+        assert_eq!((error.span.line, error.span.column), (2, 9));
+    }
+
+    #[test]
+    fn identifiers_with_digits_and_underscores() {
+        let input = "my_var x1 _ _private __init__ a1b2 let_ 1x";
+
+        let expected = vec![
+            (TokenType::IDENT, "my_var"),
+            (TokenType::IDENT, "x1"),
+            (TokenType::IDENT, "_"),
+            (TokenType::IDENT, "_private"),
+            (TokenType::IDENT, "__init__"),
+            (TokenType::IDENT, "a1b2"),
+            (TokenType::IDENT, "let_"),
+            (TokenType::INT, "1"),
+            (TokenType::IDENT, "x"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn string_tokens() {
+        let input = r#""foobar" "foo bar" "" "with \"quotes\"""#;
+
+        let expected = vec![
+            (TokenType::STRING, r#""foobar""#),
+            (TokenType::STRING, r#""foo bar""#),
+            (TokenType::STRING, r#""""#),
+            (TokenType::STRING, r#""with \"quotes\"""#),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn unescape_sequences() {
+        assert_eq!(unescape(r#"a\nb\tc\"d\\e"#).unwrap(), "a\nb\tc\"d\\e");
+        assert_eq!(unescape(r"\u{41}\u{e9}\u{1F600}").unwrap(), "Aé😀");
+
+        let tests = vec![
+            (r"ab\q", 2, 4),
+            (r"\u41", 0, 2),
+            (r"\u{}", 0, 2),
+            (r"\u{1234567}", 0, 2),
+            (r"\u{+41}", 0, 2),
+            (r"x\u{D800}", 1, 9),
+        ];
+
+        for (body, start, end) in tests {
+            let error = unescape(body).unwrap_err();
+            assert_eq!((error.start, error.end), (start, end), "{}", body);
+        }
+    }
+
+    #[test]
+    fn string_errors() {
+        let error = tokenize("let s = \"abc").unwrap_err();
+        assert_eq!(error.code, UNTERMINATED_STRING);
+        assert_eq!(error.message, "unterminated string literal");
+        assert_eq!((error.span.line, error.span.column), (1, 9));
+
+        let error = tokenize("\"ok\"\n\"tab\\x\"").unwrap_err();
+        assert_eq!(error.code, INVALID_ESCAPE);
+        assert_eq!(error.message, "unknown escape sequence `\\x`");
+        assert_eq!((error.span.line, error.span.column), (2, 5));
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let input = "// leading comment
+let x = 10; // trailing comment
+/* block */ x / /* nested /* inner */ still comment */ 2;
+//// not a doc comment
+/// doc comment
+x";
+
+        let expected = vec![
+            (TokenType::LET, "let"),
+            (TokenType::IDENT, "x"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::INT, "10"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::FSLASH, "/"),
+            (TokenType::INT, "2"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn doc_comments_as_trivia() {
+        let input = "/// Adds one.\n//// plain\nlet inc = fn(x) { x + 1 };";
+
+        let mut lexer = Lexer::new(input);
+        lexer.keep_doc_comments = true;
+        let tokens: Vec<(TokenType, &str)> = lexer
+            .take(2)
+            .map(|tok| (tok.token_type, tok.literal))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenType::DOCCOMMENT, "/// Adds one."),
+                (TokenType::LET, "let")
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let error = tokenize("1 /* open /* nested */").unwrap_err();
+
+        assert_eq!(error.code, UNTERMINATED_COMMENT);
+        assert_eq!((error.span.line, error.span.column), (1, 3));
+    }
+
+    #[test]
+    fn numeric_literals() {
+        let input = "3.14 1e-9 2.5E+3 1e10 0x1F 0XfF 0b1010 0o17 1_000_000 0x_FF 1.x 2else";
+
+        let expected = vec![
+            (TokenType::FLOAT, "3.14"),
+            (TokenType::FLOAT, "1e-9"),
+            (TokenType::FLOAT, "2.5E+3"),
+            (TokenType::FLOAT, "1e10"),
+            (TokenType::INT, "0x1F"),
+            (TokenType::INT, "0XfF"),
+            (TokenType::INT, "0b1010"),
+            (TokenType::INT, "0o17"),
+            (TokenType::INT, "1_000_000"),
+            (TokenType::INT, "0x_FF"),
+            (TokenType::INT, "1"),
+            (TokenType::ILLEGAL, "."),
+            (TokenType::IDENT, "x"),
+            (TokenType::INT, "2"),
+            (TokenType::ELSE, "else"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn missing_radix_digits() {
+        let error = tokenize("0x;").unwrap_err();
+
+        assert_eq!(error.code, INVALID_LITERAL);
+        assert_eq!(error.message, "missing digits after `0x`");
+    }
+
+    #[test]
+    fn two_character_operators() {
+        let input = "a <= b >= c % d && e || f; x += 1; x -= 1; x *= 2; x /= 2; x %= 3; & |";
+
+        let expected = vec![
+            (TokenType::IDENT, "a"),
+            (TokenType::LESSEQ, "<="),
+            (TokenType::IDENT, "b"),
+            (TokenType::GREATEREQ, ">="),
+            (TokenType::IDENT, "c"),
+            (TokenType::PERCENT, "%"),
+            (TokenType::IDENT, "d"),
+            (TokenType::AND, "&&"),
+            (TokenType::IDENT, "e"),
+            (TokenType::OR, "||"),
+            (TokenType::IDENT, "f"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::PLUSASSIGN, "+="),
+            (TokenType::INT, "1"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::MINUSASSIGN, "-="),
+            (TokenType::INT, "1"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::STARASSIGN, "*="),
+            (TokenType::INT, "2"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::FSLASHASSIGN, "/="),
+            (TokenType::INT, "2"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::IDENT, "x"),
+            (TokenType::PERCENTASSIGN, "%="),
+            (TokenType::INT, "3"),
+            (TokenType::SEMICOLON, ";"),
+            (TokenType::ILLEGAL, "&"),
+            (TokenType::ILLEGAL, "|"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, token_type);
+            assert_eq!(tok.literal, literal);
+        }
+    }
+
+    #[test]
+    fn brackets() {
+        let tokens = tokenize("[1, 2][0]").unwrap();
+        let token_types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::LBRACKET,
+                TokenType::INT,
+                TokenType::COMMA,
+                TokenType::INT,
+                TokenType::RBRACKET,
+                TokenType::LBRACKET,
+                TokenType::INT,
+                TokenType::RBRACKET,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn colon() {
+        let tokens = tokenize(r#"{"a": 1}"#).unwrap();
+        let token_types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::LBRACE,
+                TokenType::STRING,
+                TokenType::COLON,
+                TokenType::INT,
+                TokenType::RBRACE,
+                TokenType::EOF,
+            ]
+        );
     }
 }
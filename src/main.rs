@@ -1,10 +1,192 @@
-mod token;
-mod lexer;
-mod repl;
+// Token and precedence names mirror the spec's upper-case spelling.
+#![allow(
+    clippy::upper_case_acronyms,
+    clippy::module_inception,
+    clippy::enum_variant_names
+)]
+
 mod ast;
+mod diagnostic;
+mod evaluator;
+mod lexer;
 mod parser;
+mod repl;
+mod token;
+
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+    thread,
+};
+
+use diagnostic::Diagnostic;
+use evaluator::{
+    environment::Environment,
+    evaluator::{eval_program, STACK_SIZE},
+    object::Object,
+};
+use lexer::Lexer;
+use parser::parser::Parser;
+
+const USAGE: &str = "\
+usage: crust [repl]        start the interactive REPL
+       crust <file>        run a script
+       crust -e <source>   evaluate source and print the result
+       crust -             run a script read from stdin";
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Repl,
+    File(String),
+    Eval(String),
+    Stdin,
+}
+
+fn main() -> ExitCode {
+    // The main thread's stack is too small for deeply recursive programs.
+    match thread::Builder::new().stack_size(STACK_SIZE).spawn(cli) {
+        Ok(handle) => handle.join().unwrap_or(ExitCode::FAILURE),
+        Err(error) => {
+            eprintln!("error: could not start the interpreter: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn cli() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mode = match parse_args(&args) {
+        Ok(mode) => mode,
+        Err(message) => {
+            eprintln!("error: {}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match mode {
+        Mode::Repl => {
+            println!("Welcome to the crust programming language!");
+            repl::start();
+            ExitCode::SUCCESS
+        }
+        Mode::File(path) => match fs::read_to_string(&path) {
+            Ok(source) => run(&path, &source, false),
+            Err(error) => {
+                eprintln!("error: could not read {}: {}", path, error);
+                ExitCode::FAILURE
+            }
+        },
+        Mode::Eval(source) => run("<-e>", &source, true),
+        Mode::Stdin => {
+            let mut source = String::new();
+            match io::stdin().read_to_string(&mut source) {
+                Ok(_) => run("<stdin>", &source, false),
+                Err(error) => {
+                    eprintln!("error: could not read stdin: {}", error);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Mode, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [] | ["repl"] => Ok(Mode::Repl),
+        ["-e"] => Err("`-e` needs source to evaluate".to_string()),
+        ["-e", source] => Ok(Mode::Eval(source.to_string())),
+        ["-"] => Ok(Mode::Stdin),
+        [flag] if flag.starts_with('-') => Err(format!("unknown option `{}`", flag)),
+        [path] => Ok(Mode::File(path.to_string())),
+        _ => Err("too many arguments".to_string()),
+    }
+}
+
+/// Runs a whole program, reporting parse and runtime errors on stderr and
+/// through the exit code.
+fn run(name: &str, source: &str, print_result: bool) -> ExitCode {
+    let source = strip_shebang(source);
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        for error in &parser.errors {
+            eprint!("{}", error.render(name, source));
+        }
+        return ExitCode::FAILURE;
+    }
+
+    match eval_program(&program, &Environment::new()) {
+        Object::Error(error) => {
+            eprint!("{}", Diagnostic::from(error).render(name, source));
+            ExitCode::FAILURE
+        }
+        Object::Null => ExitCode::SUCCESS,
+        result => {
+            if print_result {
+                println!("{}", result);
+            }
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Blanks out a `#!` line so scripts can be executable. The newline is kept
+/// so diagnostics still report the right line numbers.
+fn strip_shebang(source: &str) -> &str {
+    if source.starts_with("#!") {
+        &source[source.find('\n').unwrap_or(source.len())..]
+    } else {
+        source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, strip_shebang, Mode};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn command_line_modes() {
+        let tests = vec![
+            (args(&[]), Ok(Mode::Repl)),
+            (args(&["repl"]), Ok(Mode::Repl)),
+            (
+                args(&["script.cr"]),
+                Ok(Mode::File("script.cr".to_string())),
+            ),
+            (args(&["-e", "1 + 2"]), Ok(Mode::Eval("1 + 2".to_string()))),
+            (args(&["-"]), Ok(Mode::Stdin)),
+            (
+                args(&["-e"]),
+                Err("`-e` needs source to evaluate".to_string()),
+            ),
+            (
+                args(&["--nope"]),
+                Err("unknown option `--nope`".to_string()),
+            ),
+            (
+                args(&["a.cr", "b.cr"]),
+                Err("too many arguments".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(parse_args(&input), expected, "{:?}", input);
+        }
+    }
 
-fn main() {
-    println!("Welcome to the crust programming language!");
-    repl::start();
+    #[test]
+    fn shebang_lines() {
+        assert_eq!(strip_shebang("#!/usr/bin/env crust\nputs(1)"), "\nputs(1)");
+        assert_eq!(strip_shebang("#!crust"), "");
+        assert_eq!(strip_shebang("puts(1)"), "puts(1)");
+    }
 }
@@ -1,24 +1,33 @@
 use crate::{
-    ast::ast::{Expression, Identifier, Precedence, Program, Statement, Statement::LetStatement},
-    lexer::new_token,
-    lexer::Lexer,
-    token::{Token, TokenType},
+    ast::ast::{
+        BlockStatement, Expression, ExpressionKind, Identifier, Precedence, Program, Statement,
+        StatementKind,
+    },
+    diagnostic::{
+        Diagnostic, EXPECTED_EXPRESSION, INVALID_ASSIGNMENT, INVALID_LITERAL, UNCLOSED_DELIMITER,
+        UNEXPECTED_TOKEN,
+    },
+    lexer::{new_token, unescape, Lexer},
+    token::{Span, Token, TokenType},
 };
 
-pub struct Parser {
-    pub lexer: Lexer,
-    pub current_token: Token,
-    pub peek_token: Token,
-    pub errors: Vec<String>,
+pub struct Parser<'a> {
+    pub lexer: Lexer<'a>,
+    pub current_token: Token<'a>,
+    pub peek_token: Token<'a>,
+    pub errors: Vec<Diagnostic>,
+    /// Span of the last token before `EOF`, where end-of-input errors point.
+    last_span: Span,
 }
 
-impl Parser {
-    pub fn new(lexer: Lexer) -> Parser {
+impl<'a> Parser<'a> {
+    pub fn new(lexer: Lexer<'a>) -> Parser<'a> {
         let mut parser = Parser {
             lexer,
-            current_token: new_token(TokenType::ILLEGAL, "".to_string()),
-            peek_token: new_token(TokenType::ILLEGAL, "".to_string()),
+            current_token: new_token(TokenType::ILLEGAL, ""),
+            peek_token: new_token(TokenType::ILLEGAL, ""),
             errors: Vec::new(),
+            last_span: Span::default(),
         };
         parser.next_token();
         parser.next_token();
@@ -28,6 +37,34 @@ impl Parser {
     pub fn next_token(&mut self) {
         self.current_token = self.peek_token.clone();
         self.peek_token = self.lexer.next_token();
+        if self.peek_token.token_type != TokenType::EOF {
+            self.last_span = self.peek_token.span;
+        }
+    }
+
+    /// Where a diagnostic about `token` should point. The `EOF` token sits
+    /// after any trailing newline, so errors about it point just past the last
+    /// real token instead of at an empty line.
+    fn error_span(&self, token: &Token) -> Span {
+        let last = self.last_span;
+        if token.token_type != TokenType::EOF || last.line == 0 {
+            return token.span;
+        }
+
+        let text = &self.lexer.input[last.start..last.end];
+        let (line, column) = match text.rsplit_once('\n') {
+            Some((before, after)) => (
+                last.line + before.matches('\n').count() + 1,
+                after.chars().count() + 1,
+            ),
+            None => (last.line, last.column + text.chars().count()),
+        };
+        Span {
+            start: last.end,
+            end: last.end,
+            line,
+            column,
+        }
     }
 
     pub fn parse_program(&mut self) -> Program {
@@ -36,10 +73,16 @@ impl Parser {
         };
 
         while self.current_token.token_type != TokenType::EOF {
-            let statement = self.parse_statement();
-            match statement {
+            match self.parse_statement() {
                 Some(stmt) => program.statements.push(stmt),
-                None => (),
+                None => {
+                    self.synchronize();
+                    // There is no block for a stray `}` to close at the top
+                    // level; skip it rather than report it a second time.
+                    if self.peek_token_is(&TokenType::RBRACE) {
+                        self.next_token();
+                    }
+                }
             }
             self.next_token();
         }
@@ -47,114 +90,577 @@ impl Parser {
         program
     }
 
-    pub fn parse_statement(&mut self) -> Option<Statement> {
-        match self.current_token.token_type {
-            TokenType::LET => {
-                return self.parse_let_statement();
-            }
-            TokenType::RETURN => {
-                return self.parse_return_statement();
+    /// Skips the rest of a statement that failed to parse, so that one mistake
+    /// produces one diagnostic. Stops on the `;` ending the statement, or just
+    /// before a token that starts a new statement; the caller then advances
+    /// past it as it would after a successful statement.
+    ///
+    /// Braces opened while skipping are skipped along with their contents. A
+    /// `}` closing the enclosing block is never consumed: this stops just
+    /// before it, or on it if the statement failed at that `}`.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+
+        loop {
+            match self.current_token.token_type {
+                TokenType::EOF => return,
+                TokenType::SEMICOLON | TokenType::RBRACE if depth == 0 => return,
+                TokenType::LBRACE => depth += 1,
+                TokenType::RBRACE => depth -= 1,
+                _ => (),
             }
-            ref token => {
-                return self.parse_expression_statement(token.clone());
+            match self.peek_token.token_type {
+                TokenType::LET | TokenType::RETURN | TokenType::RBRACE if depth == 0 => return,
+                _ => self.next_token(),
             }
         }
     }
 
+    pub fn parse_statement(&mut self) -> Option<Statement> {
+        match self.current_token.token_type {
+            TokenType::LET => self.parse_let_statement(),
+            TokenType::RETURN => self.parse_return_statement(),
+            _ => self.parse_expression_statement(),
+        }
+    }
+
     pub fn parse_let_statement(&mut self) -> Option<Statement> {
+        let start = self.current_token.span;
+
         if !self.expect_peek(TokenType::IDENT) {
             return None;
         }
 
-        let ident = Identifier(self.current_token.literal.to_string());
+        let ident = self.parse_identifier()?;
 
         if !self.expect_peek(TokenType::ASSIGN) {
             return None;
         }
 
-        while !self.current_token_is(TokenType::SEMICOLON) {
-            self.next_token();
+        self.next_token();
+        let value = self.parse_expression(Precedence::LOWEST)?;
+
+        if !self.expect_statement_end() {
+            return None;
         }
 
-        Some(Statement::LetStatement(
-            ident.clone(),
-            Expression::Identifier(ident),
+        Some(Statement::new(
+            StatementKind::LetStatement(ident, value),
+            self.span_from(start),
         ))
     }
 
     pub fn parse_return_statement(&mut self) -> Option<Statement> {
-        while !self.current_token_is(TokenType::SEMICOLON) {
-            self.next_token();
+        let start = self.current_token.span;
+        self.next_token();
+        let value = self.parse_expression(Precedence::LOWEST)?;
+
+        if !self.expect_statement_end() {
+            return None;
         }
 
-        Some(Statement::ReturnStatement(Expression::Identifier(
-            Identifier(self.current_token.literal.to_string()),
-        )))
+        Some(Statement::new(
+            StatementKind::ReturnStatement(value),
+            self.span_from(start),
+        ))
     }
 
-    pub fn parse_expression_statement(&mut self, token: TokenType) -> Option<Statement> {
-        let current = self.current_token.literal.clone();
+    pub fn parse_expression_statement(&mut self) -> Option<Statement> {
+        let start = self.current_token.span;
+        let expression = self.parse_expression(Precedence::LOWEST)?;
 
-        let expression = self.parse_expression(Precedence::LOWEST);
-        if self.peek_token_is(&TokenType::SEMICOLON) {
-            self.next_token();
+        // Like Rust, an `if` ending in a block needs no `;` to end the statement.
+        if matches!(expression.kind, ExpressionKind::If { .. }) {
+            if self.peek_token_is(&TokenType::SEMICOLON) {
+                self.next_token();
+            }
+        } else if !self.expect_statement_end() {
+            return None;
         }
-        match expression {
-            Some(expr) => Some(Statement::ExpressionStatement(expr)),
-            None => None,
+
+        Some(Statement::new(
+            StatementKind::ExpressionStatement(expression),
+            self.span_from(start),
+        ))
+    }
+
+    /// Consumes the `;` ending a statement, which may only be left out before
+    /// the end of input or a closing `}`.
+    fn expect_statement_end(&mut self) -> bool {
+        if self.peek_token_is(&TokenType::EOF) || self.peek_token_is(&TokenType::RBRACE) {
+            return true;
         }
+        self.expect_peek(TokenType::SEMICOLON)
     }
 
     pub fn parse_expression(&mut self, precedence: Precedence) -> Option<Expression> {
-        let mut prefix = self.prefix_parse(self.current_token.token_type.clone());
+        let mut left = self.prefix_parse(self.current_token.token_type.clone())?;
 
-        if prefix.is_none() {
-            self.no_prefix_error();
-            return None;
+        while !self.peek_token_is(&TokenType::SEMICOLON) && precedence < self.peek_precedence() {
+            self.next_token();
+            left = self.infix_parse(self.current_token.token_type.clone(), left)?;
+        }
+
+        Some(left)
+    }
+
+    /// Reports the lexer's explanation for the current `ILLEGAL` token.
+    fn illegal_token_error(&mut self) {
+        match self.lexer_error(&self.current_token) {
+            Some(diagnostic) => self.errors.push(diagnostic),
+            None => self.no_prefix_error(),
         }
+    }
 
-        prefix
+    /// The error the lexer recorded for an `ILLEGAL` token, if any.
+    fn lexer_error(&self, token: &Token) -> Option<Diagnostic> {
+        let span = token.span;
+        self.lexer
+            .errors
+            .iter()
+            .find(|error| error.span.start >= span.start && error.span.end <= span.end)
+            .map(|error| error.clone().into())
     }
 
     fn no_prefix_error(&mut self) {
-        let token_literal = self.peek_token.literal.clone();
-        self.errors.push(format!(
-            "no prefix parse function for {} found",
-            token_literal
-        ));
+        let span = self.error_span(&self.current_token);
+        let diagnostic = Diagnostic::error(
+            EXPECTED_EXPRESSION,
+            format!(
+                "expected an expression, found {}",
+                describe(&self.current_token)
+            ),
+            span,
+        )
+        .with_label(span, "expected an expression");
+        self.errors.push(diagnostic);
     }
 
     fn parse_integer_literal(&mut self) -> Option<Expression> {
-        let literal = self.current_token.literal.parse::<i64>();
+        let literal = self.current_token.literal.replace('_', "");
+        let (digits, radix) = match literal.get(..2) {
+            Some("0x" | "0X") => (&literal[2..], 16),
+            Some("0b" | "0B") => (&literal[2..], 2),
+            Some("0o" | "0O") => (&literal[2..], 8),
+            _ => (literal.as_str(), 10),
+        };
+
+        match i64::from_str_radix(digits, radix) {
+            Ok(value) => Some(Expression::new(
+                ExpressionKind::IntegerLiteral(value),
+                self.current_token.span,
+            )),
+            Err(_) => {
+                let diagnostic = Diagnostic::error(
+                    INVALID_LITERAL,
+                    "integer literal is too large",
+                    self.current_token.span,
+                )
+                .with_label(self.current_token.span, "does not fit in a 64-bit integer")
+                .with_note(format!("the largest integer is {}", i64::MAX));
+                self.errors.push(diagnostic);
+                None
+            }
+        }
+    }
+
+    fn parse_float_literal(&mut self) -> Option<Expression> {
+        let span = self.current_token.span;
+        // The lexer only produces well-formed literals, so parsing can only
+        // fail by overflowing to infinity.
+        let value = self
+            .current_token
+            .literal
+            .replace('_', "")
+            .parse::<f64>()
+            .ok()?;
+
+        if value.is_infinite() {
+            let diagnostic =
+                Diagnostic::error(INVALID_LITERAL, "float literal is out of range", span)
+                    .with_label(span, "does not fit in a 64-bit float")
+                    .with_note(format!("the largest float is {:e}", f64::MAX));
+            self.errors.push(diagnostic);
+            return None;
+        }
+
+        Some(Expression::new(ExpressionKind::FloatLiteral(value), span))
+    }
+
+    fn parse_string_literal(&mut self) -> Option<Expression> {
+        let literal = self.current_token.literal;
+        // The lexer has already rejected invalid escapes.
+        let value = unescape(&literal[1..literal.len() - 1]).ok()?;
+
+        Some(Expression::new(
+            ExpressionKind::StringLiteral(value),
+            self.current_token.span,
+        ))
+    }
+
+    fn parse_boolean(&mut self) -> Option<Expression> {
+        Some(Expression::new(
+            ExpressionKind::Boolean(self.current_token_is(TokenType::TRUE)),
+            self.current_token.span,
+        ))
+    }
+
+    fn parse_grouped_expression(&mut self) -> Option<Expression> {
+        let start = self.current_token.span;
+        self.next_token();
+        let expression = self.parse_expression(Precedence::LOWEST)?;
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+
+        Some(Expression::new(expression.kind, self.span_from(start)))
+    }
+
+    fn parse_if_expression(&mut self) -> Option<Expression> {
+        let start = self.current_token.span;
+
+        if !self.expect_peek(TokenType::LPAREN) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(Precedence::LOWEST)?;
+
+        if !self.expect_peek(TokenType::RPAREN) || !self.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+        let consequence = self.parse_block_statement()?;
+
+        let alternative = if self.peek_token_is(&TokenType::ELSE) {
+            self.next_token();
+            if !self.expect_peek(TokenType::LBRACE) {
+                return None;
+            }
+            Some(self.parse_block_statement()?)
+        } else {
+            None
+        };
+
+        Some(Expression::new(
+            ExpressionKind::If {
+                condition: Box::new(condition),
+                consequence,
+                alternative,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_block_statement(&mut self) -> Option<BlockStatement> {
+        let open = self.current_token.span;
+        let mut statements = Vec::new();
+        self.next_token();
+
+        while !self.current_token_is(TokenType::RBRACE) {
+            if self.current_token_is(TokenType::EOF) {
+                let span = self.error_span(&self.current_token);
+                let diagnostic =
+                    Diagnostic::error(UNCLOSED_DELIMITER, "expected `}`, found end of input", span)
+                        .with_label(span, "expected `}`")
+                        .with_label(open, "block opened here");
+                self.errors.push(diagnostic);
+                return None;
+            }
+            match self.parse_statement() {
+                Some(statement) => statements.push(statement),
+                None => {
+                    self.synchronize();
+                    // The statement failed at this block's closing brace.
+                    if self.current_token_is(TokenType::RBRACE) {
+                        continue;
+                    }
+                }
+            }
+            self.next_token();
+        }
+
+        Some(BlockStatement {
+            statements,
+            span: self.span_from(open),
+        })
+    }
+
+    fn parse_function_literal(&mut self) -> Option<Expression> {
+        let start = self.current_token.span;
+
+        if !self.expect_peek(TokenType::LPAREN) {
+            return None;
+        }
+        let params = self.parse_function_parameters()?;
+
+        if !self.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+        let body = self.parse_block_statement()?;
+
+        Some(Expression::new(
+            ExpressionKind::Function { params, body },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
+        let mut params = Vec::new();
+
+        if self.peek_token_is(&TokenType::RPAREN) {
+            self.next_token();
+            return Some(params);
+        }
+
+        if !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        params.push(self.parse_identifier()?);
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            if !self.expect_peek(TokenType::IDENT) {
+                return None;
+            }
+            params.push(self.parse_identifier()?);
+        }
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+
+        Some(params)
+    }
+
+    fn parse_call_expression(&mut self, function: Expression) -> Option<Expression> {
+        let start = function.span;
+        let args = self.parse_expression_list(TokenType::RPAREN)?;
+
+        Some(Expression::new(
+            ExpressionKind::Call {
+                function: Box::new(function),
+                args,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_array_literal(&mut self) -> Option<Expression> {
+        let start = self.current_token.span;
+        let elements = self.parse_expression_list(TokenType::RBRACKET)?;
+
+        Some(Expression::new(
+            ExpressionKind::ArrayLiteral(elements),
+            self.span_from(start),
+        ))
+    }
+
+    /// Parses `{key: value, ...}`. Blocks are only parsed where a statement
+    /// list is expected (after `if`, `else` and `fn(...)`), so a `{` in
+    /// expression position always starts a hash literal.
+    fn parse_hash_literal(&mut self) -> Option<Expression> {
+        let start = self.current_token.span;
+        let mut pairs = Vec::new();
+
+        while !self.peek_token_is(&TokenType::RBRACE) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::LOWEST)?;
 
-        match literal {
-            Ok(value) => Some(Expression::IntegerLiteral(value)),
-            Err(_) => None,
+            if !self.expect_peek(TokenType::COLON) {
+                return None;
+            }
+            self.next_token();
+            let value = self.parse_expression(Precedence::LOWEST)?;
+            pairs.push((key, value));
+
+            if !self.peek_token_is(&TokenType::RBRACE) && !self.expect_peek(TokenType::COMMA) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::RBRACE) {
+            return None;
+        }
+
+        Some(Expression::new(
+            ExpressionKind::HashLiteral(pairs),
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+        let start = left.span;
+        self.next_token();
+        let index = self.parse_expression(Precedence::LOWEST)?;
+
+        if !self.expect_peek(TokenType::RBRACKET) {
+            return None;
+        }
+
+        Some(Expression::new(
+            ExpressionKind::Index {
+                left: Box::new(left),
+                index: Box::new(index),
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// Parses comma-separated expressions up to and including the `end` token.
+    fn parse_expression_list(&mut self, end: TokenType) -> Option<Vec<Expression>> {
+        let mut list = Vec::new();
+
+        if self.peek_token_is(&end) {
+            self.next_token();
+            return Some(list);
+        }
+
+        self.next_token();
+        list.push(self.parse_expression(Precedence::LOWEST)?);
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Precedence::LOWEST)?);
+        }
+
+        if !self.expect_peek(end) {
+            return None;
         }
+
+        Some(list)
     }
 
     fn parse_prefix_expression(&mut self, token: TokenType) -> Option<Expression> {
-        None
+        let start = self.current_token.span;
+        self.next_token();
+        let right = self.parse_expression(Precedence::PREFIX)?;
+
+        Some(Expression::new(
+            ExpressionKind::Prefix(token, Box::new(right)),
+            self.span_from(start),
+        ))
     }
 
     fn prefix_parse(&mut self, token: TokenType) -> Option<Expression> {
         match token {
             TokenType::IDENT => {
-                let ident = self.parse_identifier();
-                match ident {
-                    Some(ident) => Some(Expression::Identifier(ident)),
-                    None => None,
-                }
+                let ident = self.parse_identifier()?;
+                Some(Expression::new(
+                    ExpressionKind::Identifier(ident),
+                    self.current_token.span,
+                ))
             }
             TokenType::INT => self.parse_integer_literal(),
+            TokenType::FLOAT => self.parse_float_literal(),
+            TokenType::TRUE | TokenType::FALSE => self.parse_boolean(),
+            TokenType::STRING => self.parse_string_literal(),
+            TokenType::ILLEGAL => {
+                self.illegal_token_error();
+                None
+            }
+            TokenType::LPAREN => self.parse_grouped_expression(),
+            TokenType::IF => self.parse_if_expression(),
+            TokenType::FUNCTION => self.parse_function_literal(),
+            TokenType::LBRACKET => self.parse_array_literal(),
+            TokenType::LBRACE => self.parse_hash_literal(),
             TokenType::BANG => self.parse_prefix_expression(token),
             TokenType::MINUS => self.parse_prefix_expression(token),
-            _ => None,
+            _ => {
+                self.no_prefix_error();
+                None
+            }
+        }
+    }
+
+    fn parse_infix_expression(&mut self, left: Expression) -> Option<Expression> {
+        let start = left.span;
+        let operator = self.current_token.token_type.clone();
+        let precedence = self.current_precedence();
+        self.next_token();
+        let right = self.parse_expression(precedence)?;
+
+        Some(Expression::new(
+            ExpressionKind::Infix(Box::new(left), operator, Box::new(right)),
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_assign_expression(&mut self, left: Expression) -> Option<Expression> {
+        let operator = self.current_token.token_type.clone();
+        let name = match left.kind {
+            ExpressionKind::Identifier(name) => name,
+            _ => {
+                let diagnostic = Diagnostic::error(
+                    INVALID_ASSIGNMENT,
+                    format!("invalid left-hand side of `{}`", operator),
+                    left.span,
+                )
+                .with_label(left.span, "cannot assign to this expression")
+                .with_note("only variables can be assigned to");
+                self.errors.push(diagnostic);
+                return None;
+            }
+        };
+        self.next_token();
+        // Parsing the value at the lowest precedence makes assignment
+        // right-associative: `a += b += 1` is `a += (b += 1)`.
+        let value = self.parse_expression(Precedence::LOWEST)?;
+
+        Some(Expression::new(
+            ExpressionKind::Assign {
+                name,
+                operator,
+                value: Box::new(value),
+            },
+            self.span_from(left.span),
+        ))
+    }
+
+    fn infix_parse(&mut self, token: TokenType, left: Expression) -> Option<Expression> {
+        match token {
+            TokenType::PLUS
+            | TokenType::MINUS
+            | TokenType::STAR
+            | TokenType::FSLASH
+            | TokenType::PERCENT
+            | TokenType::LESS
+            | TokenType::GREATER
+            | TokenType::LESSEQ
+            | TokenType::GREATEREQ
+            | TokenType::EQ
+            | TokenType::NOTEQ
+            | TokenType::AND
+            | TokenType::OR => self.parse_infix_expression(left),
+            TokenType::PLUSASSIGN
+            | TokenType::MINUSASSIGN
+            | TokenType::STARASSIGN
+            | TokenType::FSLASHASSIGN
+            | TokenType::PERCENTASSIGN => self.parse_assign_expression(left),
+            TokenType::LPAREN => self.parse_call_expression(left),
+            TokenType::LBRACKET => self.parse_index_expression(left),
+            _ => Some(left),
         }
     }
 
+    fn peek_precedence(&self) -> Precedence {
+        precedence_of(&self.peek_token.token_type)
+    }
+
+    fn current_precedence(&self) -> Precedence {
+        precedence_of(&self.current_token.token_type)
+    }
+
     fn parse_identifier(&mut self) -> Option<Identifier> {
-        Some(Identifier(self.current_token.literal.to_string()))
+        Some(Identifier {
+            name: self.current_token.literal.to_string(),
+            span: self.current_token.span,
+        })
+    }
+
+    /// Span from `start` up to and including the current token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.current_token.span)
     }
 
     fn current_token_is(&self, t: TokenType) -> bool {
@@ -168,26 +674,92 @@ impl Parser {
     fn expect_peek(&mut self, token: TokenType) -> bool {
         if self.peek_token_is(&token) {
             self.next_token();
-            return true;
+            true
         } else {
             self.peek_error(&token);
-            return false;
+            false
         }
     }
 
     fn peek_error(&mut self, t: &TokenType) {
-        let token_literal = self.peek_token.literal.clone();
-        self.errors.push(format!(
-            "expected {:?}, but got {} instead",
-            t, token_literal
-        ));
+        // An invalid token is better explained by the lexer than by what
+        // the parser expected in its place.
+        if self.peek_token.token_type == TokenType::ILLEGAL {
+            if let Some(diagnostic) = self.lexer_error(&self.peek_token) {
+                self.errors.push(diagnostic);
+                return;
+            }
+        }
+
+        let expected = describe_type(t);
+        let span = self.error_span(&self.peek_token);
+        let diagnostic = Diagnostic::error(
+            UNEXPECTED_TOKEN,
+            format!(
+                "expected {}, found {}",
+                expected,
+                describe(&self.peek_token)
+            ),
+            span,
+        )
+        .with_label(span, format!("expected {}", expected));
+        self.errors.push(diagnostic);
+    }
+}
+
+/// Quotes a token for a message. Only the first line of a multi-line token,
+/// such as an unterminated comment, is shown.
+fn describe(token: &Token) -> String {
+    match token.token_type {
+        TokenType::EOF => "end of input".to_string(),
+        _ => match token.literal.split_once('\n') {
+            Some((first, _)) => format!("`{}...`", first.trim_end()),
+            None => format!("`{}`", token.literal),
+        },
+    }
+}
+
+fn describe_type(token: &TokenType) -> String {
+    match token {
+        TokenType::IDENT => "an identifier".to_string(),
+        TokenType::INT => "an integer".to_string(),
+        TokenType::FLOAT => "a float".to_string(),
+        TokenType::STRING => "a string".to_string(),
+        TokenType::EOF => "end of input".to_string(),
+        _ => format!("`{}`", token),
+    }
+}
+
+fn precedence_of(token: &TokenType) -> Precedence {
+    match token {
+        TokenType::PLUSASSIGN
+        | TokenType::MINUSASSIGN
+        | TokenType::STARASSIGN
+        | TokenType::FSLASHASSIGN
+        | TokenType::PERCENTASSIGN => Precedence::ASSIGN,
+        TokenType::OR => Precedence::OR,
+        TokenType::AND => Precedence::AND,
+        TokenType::EQ | TokenType::NOTEQ => Precedence::EQUALS,
+        TokenType::LESS | TokenType::GREATER | TokenType::LESSEQ | TokenType::GREATEREQ => {
+            Precedence::LESSGREATER
+        }
+        TokenType::PLUS | TokenType::MINUS => Precedence::SUM,
+        TokenType::STAR | TokenType::FSLASH | TokenType::PERCENT => Precedence::PRODUCT,
+        TokenType::LPAREN => Precedence::CALL,
+        TokenType::LBRACKET => Precedence::INDEX,
+        _ => Precedence::LOWEST,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        ast::ast::{Expression, Identifier, Statement},
+        ast::ast::{BlockStatement, ExpressionKind, Identifier, StatementKind},
+        diagnostic::{
+            EXPECTED_EXPRESSION, INVALID_ASSIGNMENT, INVALID_CHARACTER, INVALID_ESCAPE,
+            INVALID_LITERAL, UNCLOSED_DELIMITER, UNEXPECTED_TOKEN, UNTERMINATED_COMMENT,
+            UNTERMINATED_STRING,
+        },
         lexer::Lexer,
         token::TokenType,
     };
@@ -197,7 +769,7 @@ mod tests {
         let input = "
 let x = 5;
    let y = 10;
-   let foobar = 838383;
+   let foobar = y;
 ";
 
         let lexer = Lexer::new(input);
@@ -210,18 +782,21 @@ let x = 5;
         }
 
         let expected = vec![
-            Statement::LetStatement(
-                Identifier("x".to_string()),
-                Expression::Identifier(Identifier("x".to_string())),
-            ),
-            Statement::LetStatement(
-                Identifier("y".to_string()),
-                Expression::Identifier(Identifier("y".to_string())),
-            ),
-            Statement::LetStatement(
-                Identifier("foobar".to_string()),
-                Expression::Identifier(Identifier("foobar".to_string())),
-            ),
+            StatementKind::LetStatement(
+                Identifier::from("x"),
+                ExpressionKind::IntegerLiteral(5).into(),
+            )
+            .into(),
+            StatementKind::LetStatement(
+                Identifier::from("y"),
+                ExpressionKind::IntegerLiteral(10).into(),
+            )
+            .into(),
+            StatementKind::LetStatement(
+                Identifier::from("foobar"),
+                ExpressionKind::Identifier(Identifier::from("y")).into(),
+            )
+            .into(),
         ];
 
         assert_eq!(program.statements, expected);
@@ -241,6 +816,12 @@ let foobar 838383;
         parser.parse_program();
 
         assert!(parser.errors.len() == 3);
+        assert_eq!(parser.errors[0].message, "expected `=`, found `5`");
+        assert_eq!(parser.errors[0].code, UNEXPECTED_TOKEN);
+        assert_eq!(
+            (parser.errors[0].span.line, parser.errors[0].span.column),
+            (2, 7)
+        );
     }
 
     #[test]
@@ -248,7 +829,7 @@ let foobar 838383;
         let input = "
 return 5;
 return 10;
-return (add(5, 10));
+return 5 + 10;
 ";
 
         let lexer = Lexer::new(input);
@@ -260,20 +841,40 @@ return (add(5, 10));
             panic!("program.statements does not contain 3 statements");
         }
 
-        if parser.errors.len() != 0 {
+        if !parser.errors.is_empty() {
             panic!("parser has {} errors", parser.errors.len());
         }
 
-        //TODO
         let expected = vec![
-            Statement::ReturnStatement(Expression::Identifier(Identifier("5".to_string()))),
-            // Statement::ReturnStatement(Expression("".to_string())),
-            // Statement::ReturnStatement(Expression("".to_string())),
+            StatementKind::ReturnStatement(ExpressionKind::IntegerLiteral(5).into()).into(),
+            StatementKind::ReturnStatement(ExpressionKind::IntegerLiteral(10).into()).into(),
+            StatementKind::ReturnStatement(
+                ExpressionKind::Infix(
+                    Box::new(ExpressionKind::IntegerLiteral(5).into()),
+                    TokenType::PLUS,
+                    Box::new(ExpressionKind::IntegerLiteral(10).into()),
+                )
+                .into(),
+            )
+            .into(),
         ];
 
         assert_eq!(program.statements, expected);
     }
 
+    #[test]
+    fn statements_without_trailing_semicolon() {
+        let input = "let x = 1 + 2; return x";
+
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert!(parser.errors.is_empty());
+        assert_eq!(program.to_string(), "let x = (1 + 2);return x;");
+    }
+
     #[test]
     fn identifiers() {
         let input = "foobar;";
@@ -285,10 +886,10 @@ return (add(5, 10));
         if program.statements.len() != 1 {
             panic!("program.statements does not contain 1 statements");
         }
-        let expected = vec![Statement::LetStatement(
-            Identifier("foobar".to_string()),
-            Expression::Identifier(Identifier("foobar".to_string())),
-        )];
+        let expected = vec![StatementKind::ExpressionStatement(
+            ExpressionKind::Identifier(Identifier::from("foobar")).into(),
+        )
+        .into()];
         assert_eq!(program.statements, expected);
     }
 
@@ -304,9 +905,10 @@ return (add(5, 10));
             panic!("program.statements does not contain 1 statements");
         }
 
-        let expected = vec![Statement::ExpressionStatement(Expression::IntegerLiteral(
-            5,
-        ))];
+        let expected =
+            vec![
+                StatementKind::ExpressionStatement(ExpressionKind::IntegerLiteral(5).into()).into(),
+            ];
 
         assert_eq!(program.statements, expected);
     }
@@ -322,11 +924,773 @@ return (add(5, 10));
             panic!("program.statements does not contain 1 statements");
         }
 
-        let expected = vec![Statement::ExpressionStatement(Expression::Prefix(
-            TokenType::BANG,
-            Box::new(Expression::IntegerLiteral(5)),
-        ))];
+        let expected = vec![StatementKind::ExpressionStatement(
+            ExpressionKind::Prefix(
+                TokenType::BANG,
+                Box::new(ExpressionKind::IntegerLiteral(5).into()),
+            )
+            .into(),
+        )
+        .into()];
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn infix_expressions() {
+        let tests = vec![
+            ("5 + 5;", TokenType::PLUS),
+            ("5 - 5;", TokenType::MINUS),
+            ("5 * 5;", TokenType::STAR),
+            ("5 / 5;", TokenType::FSLASH),
+            ("5 > 5;", TokenType::GREATER),
+            ("5 < 5;", TokenType::LESS),
+            ("5 == 5;", TokenType::EQ),
+            ("5 != 5;", TokenType::NOTEQ),
+        ];
+
+        for (input, operator) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+            let program = parser.parse_program();
+
+            let expected = vec![StatementKind::ExpressionStatement(
+                ExpressionKind::Infix(
+                    Box::new(ExpressionKind::IntegerLiteral(5).into()),
+                    operator,
+                    Box::new(ExpressionKind::IntegerLiteral(5).into()),
+                )
+                .into(),
+            )
+            .into()];
+
+            assert_eq!(program.statements, expected);
+        }
+    }
+
+    #[test]
+    fn operator_precedence() {
+        let tests = vec![
+            ("a + b + c", "((a + b) + c)"),
+            ("a + b - c", "((a + b) - c)"),
+            ("a * b * c", "((a * b) * c)"),
+            ("a + b / c", "(a + (b / c))"),
+            ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
+            ("3 + 4; 5 * 5", "(3 + 4)(5 * 5)"),
+            ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
+            ("1 + 2 * 3 == 7", "((1 + (2 * 3)) == 7)"),
+            ("true", "true"),
+            ("3 > 5 == false", "((3 > 5) == false)"),
+            ("(1 + 2) * 3", "((1 + 2) * 3)"),
+            ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)"),
+            ("-(5 + 5)", "(-(5 + 5))"),
+            ("!(true == true)", "(!(true == true))"),
+            ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+            (
+                "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
+                "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))",
+            ),
+            (
+                "add(a + b + c * d / f + g)",
+                "add((((a + b) + ((c * d) / f)) + g))",
+            ),
+            ("a % b * c", "((a % b) * c)"),
+            ("a + b <= c * d", "((a + b) <= (c * d))"),
+            ("a >= b == c <= d", "((a >= b) == (c <= d))"),
+            ("a || b && c", "(a || (b && c))"),
+            ("a && b || c && d", "((a && b) || (c && d))"),
+            ("a == b && c != d", "((a == b) && (c != d))"),
+            ("x += 1 + 2", "(x += (1 + 2))"),
+            ("x *= y -= 2", "(x *= (y -= 2))"),
+            ("x %= a || b", "(x %= (a || b))"),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+            ),
+            ("-a[0]", "(-(a[0]))"),
+            ("f(x)[0]", "(f(x)[0])"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+            let program = parser.parse_program();
+
+            assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn nested_prefix_expressions() {
+        let tests = vec![
+            ("-a * b", "((-a) * b)"),
+            ("!-a", "(!(-a))"),
+            ("--5", "(-(-5))"),
+            ("-a + !b", "((-a) + (!b))"),
+            ("5 > -4 == !1", "((5 > (-4)) == (!1))"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+            let program = parser.parse_program();
+
+            assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn boolean_expressions() {
+        let input = "true; false; !true;";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        let expected = vec![
+            StatementKind::ExpressionStatement(ExpressionKind::Boolean(true).into()).into(),
+            StatementKind::ExpressionStatement(ExpressionKind::Boolean(false).into()).into(),
+            StatementKind::ExpressionStatement(
+                ExpressionKind::Prefix(
+                    TokenType::BANG,
+                    Box::new(ExpressionKind::Boolean(true).into()),
+                )
+                .into(),
+            )
+            .into(),
+        ];
 
         assert_eq!(program.statements, expected);
     }
+
+    #[test]
+    fn unclosed_grouped_expression() {
+        let input = "(1 + 2;";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 1);
+    }
+
+    #[test]
+    fn if_expression() {
+        let input = "if (x < y) { x }";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = vec![StatementKind::ExpressionStatement(
+            ExpressionKind::If {
+                condition: Box::new(
+                    ExpressionKind::Infix(
+                        Box::new(ExpressionKind::Identifier(Identifier::from("x")).into()),
+                        TokenType::LESS,
+                        Box::new(ExpressionKind::Identifier(Identifier::from("y")).into()),
+                    )
+                    .into(),
+                ),
+                consequence: BlockStatement::from(vec![StatementKind::ExpressionStatement(
+                    ExpressionKind::Identifier(Identifier::from("x")).into(),
+                )
+                .into()]),
+                alternative: None,
+            }
+            .into(),
+        )
+        .into()];
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn if_else_expression() {
+        let input = "if (x < y) { x } else { let z = y; z }";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        assert_eq!(
+            program.to_string(),
+            "if (x < y) { x } else { let z = y; z }"
+        );
+    }
+
+    #[test]
+    fn unterminated_block() {
+        let input = "if (x) { x";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert!(program.statements.is_empty());
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(parser.errors[0].code, UNCLOSED_DELIMITER);
+        assert_eq!(parser.errors[0].labels[1].span.column, 8);
+    }
+
+    #[test]
+    fn function_literal() {
+        let input = "fn(x, y) { x + y; }";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = vec![StatementKind::ExpressionStatement(
+            ExpressionKind::Function {
+                params: vec![Identifier::from("x"), Identifier::from("y")],
+                body: BlockStatement::from(vec![StatementKind::ExpressionStatement(
+                    ExpressionKind::Infix(
+                        Box::new(ExpressionKind::Identifier(Identifier::from("x")).into()),
+                        TokenType::PLUS,
+                        Box::new(ExpressionKind::Identifier(Identifier::from("y")).into()),
+                    )
+                    .into(),
+                )
+                .into()]),
+            }
+            .into(),
+        )
+        .into()];
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn function_parameters() {
+        let tests = vec![
+            ("fn() {};", "fn() { }"),
+            ("fn(x) {};", "fn(x) { }"),
+            ("fn(x, y, z) {};", "fn(x, y, z) { }"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+            let program = parser.parse_program();
+
+            assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn call_expression() {
+        let input = "return (add(5, 10));";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = vec![StatementKind::ReturnStatement(
+            ExpressionKind::Call {
+                function: Box::new(ExpressionKind::Identifier(Identifier::from("add")).into()),
+                args: vec![
+                    ExpressionKind::IntegerLiteral(5).into(),
+                    ExpressionKind::IntegerLiteral(10).into(),
+                ],
+            }
+            .into(),
+        )
+        .into()];
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn node_spans() {
+        let input = "let x = 1 + 2;\n  add(x, -y)";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let let_statement = &program.statements[0];
+        assert_eq!((let_statement.span.start, let_statement.span.end), (0, 14));
+        match &let_statement.kind {
+            StatementKind::LetStatement(_, value) => {
+                assert_eq!((value.span.start, value.span.end), (8, 13));
+            }
+            other => panic!("expected a let statement, got {:?}", other),
+        }
+
+        let call_statement = &program.statements[1];
+        assert_eq!(call_statement.span.line, 2);
+        assert_eq!(call_statement.span.column, 3);
+        match &call_statement.kind {
+            StatementKind::ExpressionStatement(call) => {
+                assert_eq!(&input[call.span.start..call.span.end], "add(x, -y)");
+                match &call.kind {
+                    ExpressionKind::Call { args, .. } => {
+                        assert_eq!(&input[args[1].span.start..args[1].span.end], "-y");
+                    }
+                    other => panic!("expected a call expression, got {:?}", other),
+                }
+            }
+            other => panic!("expected an expression statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn identifier_and_block_spans() {
+        let input = "let add = fn(a, b) { a + b };";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let StatementKind::LetStatement(name, value) = &program.statements[0].kind else {
+            panic!("expected a let statement, got {:?}", program.statements[0]);
+        };
+        assert_eq!(&input[name.span.start..name.span.end], "add");
+
+        let ExpressionKind::Function { params, body } = &value.kind else {
+            panic!("expected a function literal, got {:?}", value);
+        };
+        assert_eq!(&input[params[1].span.start..params[1].span.end], "b");
+        assert_eq!(&input[body.span.start..body.span.end], "{ a + b }");
+    }
+
+    #[test]
+    fn missing_expression_reports_current_token() {
+        let input = "let x = );";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        parser.parse_program();
+
+        let error = &parser.errors[0];
+        assert_eq!(error.code, EXPECTED_EXPRESSION);
+        assert_eq!(error.message, "expected an expression, found `)`");
+        assert_eq!(error.span.column, 9);
+    }
+
+    #[test]
+    fn integer_literal_overflow() {
+        let input = "99999999999999999999;";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert!(program.statements.is_empty());
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(parser.errors[0].code, INVALID_LITERAL);
+    }
+
+    #[test]
+    fn rendered_parse_error() {
+        let input = "let x = 1;\nlet y 5;";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        parser.parse_program();
+
+        let expected = "\
+error[E0001]: expected `=`, found `5`
+ --> <repl>:2:7
+  |
+2 | let y 5;
+  |       ^ expected `=`
+";
+        assert_eq!(parser.errors[0].render("<repl>", input), expected);
+    }
+
+    #[test]
+    fn end_of_input_errors_point_after_last_token() {
+        let tests = vec![
+            (
+                "let x = 1 +\n",
+                "\
+error[E0002]: expected an expression, found end of input
+ --> t.cr:1:12
+  |
+1 | let x = 1 +
+  |            ^ expected an expression
+",
+            ),
+            (
+                "let f = fn(x) {\n  x\n",
+                "\
+error[E0003]: expected `}`, found end of input
+ --> t.cr:2:4
+  |
+1 | let f = fn(x) {
+  |               - block opened here
+2 |   x
+  |    ^ expected `}`
+",
+            ),
+            (
+                "add(1,\n  \"a\nb\"\n\n",
+                "\
+error[E0001]: expected `)`, found end of input
+ --> t.cr:3:3
+  |
+3 | b\"
+  |   ^ expected `)`
+",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "{:?}", parser.errors);
+            assert_eq!(parser.errors[0].render("t.cr", input), expected);
+        }
+    }
+
+    #[test]
+    fn recovers_at_statement_boundaries() {
+        let input = "
+let x = ;
+let y = 5;
+return ) 1 2;
+let = 10
+let z = y;
+";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        let lines: Vec<usize> = parser.errors.iter().map(|error| error.span.line).collect();
+        assert_eq!(lines, vec![2, 4, 5]);
+        assert_eq!(program.to_string(), "let y = 5;let z = y;");
+    }
+
+    #[test]
+    fn recovers_inside_blocks() {
+        let input = "let f = fn() { let = 1; x }; f";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(program.to_string(), "let f = fn() { x };f");
+    }
+
+    #[test]
+    fn one_error_per_mistake_around_braces() {
+        let tests = vec![
+            ("fn() { 1 + }", EXPECTED_EXPRESSION),
+            ("if (x { 1 }", UNEXPECTED_TOKEN),
+            ("let f = fn(x { x }; f(1)", UNEXPECTED_TOKEN),
+            (
+                "let f = fn() { let g = fn(x { x }; 1 }; f()",
+                UNEXPECTED_TOKEN,
+            ),
+        ];
+
+        for (input, code) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "{}: {:?}", input, parser.errors);
+            assert_eq!(parser.errors[0].code, code, "{}", input);
+        }
+    }
+
+    #[test]
+    fn recovery_keeps_following_statements() {
+        let input = "let f = fn(x { x }; let y = 2; fn() { 1 + }; let z = 3;";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 2, "{:?}", parser.errors);
+        assert_eq!(program.to_string(), "let y = 2;fn() { }let z = 3;");
+    }
+
+    #[test]
+    fn return_at_end_of_input() {
+        let input = "return";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        assert!(program.statements.is_empty());
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(
+            parser.errors[0].message,
+            "expected an expression, found end of input"
+        );
+    }
+
+    #[test]
+    fn string_literal() {
+        let input = r#""hello\tworld\n" + "say \"hi\" \\ \u{1F600}";"#;
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = vec![StatementKind::ExpressionStatement(
+            ExpressionKind::Infix(
+                Box::new(ExpressionKind::StringLiteral("hello\tworld\n".to_string()).into()),
+                TokenType::PLUS,
+                Box::new(ExpressionKind::StringLiteral("say \"hi\" \\ 😀".to_string()).into()),
+            )
+            .into(),
+        )
+        .into()];
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn multi_line_tokens_are_quoted_by_first_line() {
+        let lexer = Lexer::new("let x = 1 \"a\nb\"");
+        let mut parser = super::Parser::new(lexer);
+
+        parser.parse_program();
+
+        assert_eq!(parser.errors[0].message, "expected `;`, found `\"a...`");
+    }
+
+    #[test]
+    fn lexer_errors_become_diagnostics() {
+        let tests = vec![
+            ("let x = @;", INVALID_CHARACTER, (1, 9)),
+            ("let s = \"a\\qb\";", INVALID_ESCAPE, (1, 11)),
+            ("let s = \"a\\u{110000}\";", INVALID_ESCAPE, (1, 11)),
+            ("let x = 1;\n  \"abc", UNTERMINATED_STRING, (2, 3)),
+            // After an expression, where the parser expected a `;`.
+            ("1 /* open", UNTERMINATED_COMMENT, (1, 3)),
+            (
+                "let x = 1\n/* start\nline two\n",
+                UNTERMINATED_COMMENT,
+                (2, 1),
+            ),
+            ("puts(1) \"abc\nmore\n", UNTERMINATED_STRING, (1, 9)),
+        ];
+
+        for (input, code, position) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "{}", input);
+            let error = &parser.errors[0];
+            assert_eq!(error.code, code, "{}", input);
+            assert_eq!((error.span.line, error.span.column), position, "{}", input);
+        }
+    }
+
+    #[test]
+    fn numeric_literals() {
+        let input = "0x1F; 0b1010; 0o17; 1_000_000; 2.75; 1e-9; 2_500.5";
+        let lexer = Lexer::new(input);
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = vec![
+            ExpressionKind::IntegerLiteral(31),
+            ExpressionKind::IntegerLiteral(10),
+            ExpressionKind::IntegerLiteral(15),
+            ExpressionKind::IntegerLiteral(1_000_000),
+            ExpressionKind::FloatLiteral(2.75),
+            ExpressionKind::FloatLiteral(1e-9),
+            ExpressionKind::FloatLiteral(2500.5),
+        ];
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|kind| StatementKind::ExpressionStatement(kind.into()).into())
+            .collect();
+
+        assert_eq!(program.statements, expected);
+    }
+
+    #[test]
+    fn numeric_literal_overflow() {
+        let tests = vec![
+            ("0xFFFFFFFFFFFFFFFFF", "integer literal is too large"),
+            ("9_223_372_036_854_775_808", "integer literal is too large"),
+            ("1e999", "float literal is out of range"),
+        ];
+
+        for (input, message) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "{}", input);
+            assert_eq!(parser.errors[0].code, INVALID_LITERAL);
+            assert_eq!(parser.errors[0].message, message);
+        }
+    }
+
+    #[test]
+    fn invalid_assignment_target() {
+        let lexer = Lexer::new("1 + 2 += 3;");
+        let mut parser = super::Parser::new(lexer);
+
+        parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 1, "{:?}", parser.errors);
+        assert_eq!(parser.errors[0].code, INVALID_ASSIGNMENT);
+        assert_eq!(parser.errors[0].message, "invalid left-hand side of `+=`");
+        assert_eq!(parser.errors[0].span.start, 0);
+        assert_eq!(parser.errors[0].span.end, 5);
+    }
+
+    #[test]
+    fn array_literals() {
+        let lexer = Lexer::new("[1, 2 * 2, []]");
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = ExpressionKind::ArrayLiteral(vec![
+            ExpressionKind::IntegerLiteral(1).into(),
+            ExpressionKind::Infix(
+                Box::new(ExpressionKind::IntegerLiteral(2).into()),
+                TokenType::STAR,
+                Box::new(ExpressionKind::IntegerLiteral(2).into()),
+            )
+            .into(),
+            ExpressionKind::ArrayLiteral(vec![]).into(),
+        ]);
+
+        assert_eq!(
+            program.statements,
+            vec![StatementKind::ExpressionStatement(expected.into()).into()]
+        );
+        assert_eq!(program.statements[0].span.end, 14);
+    }
+
+    #[test]
+    fn index_expressions() {
+        let lexer = Lexer::new("items[1 + 1]");
+        let mut parser = super::Parser::new(lexer);
+
+        let program = parser.parse_program();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        let expected = ExpressionKind::Index {
+            left: Box::new(ExpressionKind::Identifier(Identifier::from("items")).into()),
+            index: Box::new(
+                ExpressionKind::Infix(
+                    Box::new(ExpressionKind::IntegerLiteral(1).into()),
+                    TokenType::PLUS,
+                    Box::new(ExpressionKind::IntegerLiteral(1).into()),
+                )
+                .into(),
+            ),
+        };
+
+        assert_eq!(
+            program.statements,
+            vec![StatementKind::ExpressionStatement(expected.into()).into()]
+        );
+    }
+
+    #[test]
+    fn hash_literals() {
+        let tests = vec![
+            ("{}", "{}"),
+            (r#"{"name": "x", 1: true}"#, r#"{"name": "x", 1: true}"#),
+            (
+                r#"{"one": 0 + 1, "two": 10 - 8}"#,
+                r#"{"one": (0 + 1), "two": (10 - 8)}"#,
+            ),
+            (r#"{"a": {"b": [1]}}["a"]"#, r#"({"a": {"b": [1]}}["a"])"#),
+            ("if (x) { {1: 2} }", "if x { {1: 2} }"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+            let program = parser.parse_program();
+
+            assert!(parser.errors.is_empty(), "{}: {:?}", input, parser.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn hash_literal_errors() {
+        let tests = vec![
+            (r#"{"a" 1}"#, "expected `:`, found `1`"),
+            (r#"{"a": 1 "b": 2}"#, "expected `,`, found `\"b\"`"),
+        ];
+
+        for (input, message) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert!(!parser.errors.is_empty(), "{}", input);
+            assert_eq!(parser.errors[0].message, message, "{}", input);
+        }
+    }
+
+    #[test]
+    fn missing_semicolons() {
+        let accepted = vec![
+            "let a = 1",
+            "let f = fn() { return 1 }",
+            "let f = fn() { 1 }; f()",
+            "if (x) { 1 } let a = 2;",
+        ];
+
+        for input in accepted {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert!(parser.errors.is_empty(), "{}: {:?}", input, parser.errors);
+        }
+
+        let rejected = vec![
+            ("let a = 1 let b = 2", "expected `;`, found `let`"),
+            ("return 1 2", "expected `;`, found `2`"),
+            ("a b", "expected `;`, found `b`"),
+        ];
+
+        for (input, message) in rejected {
+            let lexer = Lexer::new(input);
+            let mut parser = super::Parser::new(lexer);
+
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "{}: {:?}", input, parser.errors);
+            assert_eq!(parser.errors[0].code, UNEXPECTED_TOKEN);
+            assert_eq!(parser.errors[0].message, message);
+        }
+    }
 }
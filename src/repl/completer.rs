@@ -0,0 +1,111 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, validate::Validator, Context,
+    Helper,
+};
+
+use crate::{
+    evaluator::{builtins::BUILTINS, environment::Environment},
+    lexer::{is_identifier_char, KEYWORDS},
+};
+
+/// Completes the identifier before the cursor with a keyword, builtin or
+/// session binding.
+pub struct CrustHelper {
+    /// The session environment; swapped out when the session is reset.
+    pub env: Rc<RefCell<Environment>>,
+}
+
+impl CrustHelper {
+    fn candidates(&self, prefix: &str) -> Vec<String> {
+        let env = self.env.borrow();
+        let keywords = KEYWORDS.iter().map(|(keyword, _)| *keyword);
+        let builtins = BUILTINS.iter().map(|builtin| builtin.name);
+        let bindings = env.bindings().map(|(name, _)| name);
+
+        let mut candidates: Vec<String> = keywords
+            .chain(builtins)
+            .chain(bindings)
+            .filter(|name| name.starts_with(prefix))
+            .map(str::to_string)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+impl Completer for CrustHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|(_, ch)| is_identifier_char(*ch))
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let prefix = &line[start..pos];
+
+        // Identifiers cannot start with a digit, so neither can completions.
+        if prefix.is_empty() || prefix.starts_with(|ch: char| ch.is_ascii_digit()) {
+            return Ok((pos, Vec::new()));
+        }
+        Ok((start, self.candidates(prefix)))
+    }
+}
+
+impl Hinter for CrustHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CrustHelper {}
+
+impl Validator for CrustHelper {}
+
+impl Helper for CrustHelper {}
+
+#[cfg(test)]
+mod tests {
+    use rustyline::{history::DefaultHistory, Context};
+
+    use crate::evaluator::{environment::Environment, object::Object};
+
+    use super::{Completer, CrustHelper};
+
+    fn complete(helper: &CrustHelper, line: &str) -> (usize, Vec<String>) {
+        let history = DefaultHistory::new();
+        helper
+            .complete(line, line.len(), &Context::new(&history))
+            .unwrap()
+    }
+
+    #[test]
+    fn completes_keywords_builtins_and_bindings() {
+        let env = Environment::new();
+        env.borrow_mut().set("length", Object::Integer(1));
+        env.borrow_mut().set("total", Object::Integer(2));
+        let helper = CrustHelper { env };
+
+        assert_eq!(
+            complete(&helper, "le"),
+            (
+                0,
+                vec!["len".to_string(), "length".to_string(), "let".to_string()]
+            )
+        );
+        assert_eq!(complete(&helper, "push(to"), (5, vec!["total".to_string()]));
+        assert_eq!(
+            complete(&helper, "if (x) { ret"),
+            (9, vec!["return".to_string()])
+        );
+        assert_eq!(complete(&helper, "1 + "), (4, vec![]));
+        assert_eq!(complete(&helper, "x + 12"), (6, vec![]));
+    }
+}
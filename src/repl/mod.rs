@@ -1,2 +1,3 @@
+pub mod completer;
 pub mod repl;
 pub use repl::start;
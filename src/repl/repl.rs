@@ -1,22 +1,282 @@
-use std::io::{self, Write};
+use std::{cell::RefCell, env, fs, path::PathBuf, rc::Rc, time::Instant};
 
-use crate::lexer::Lexer;
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+
+use crate::{
+    diagnostic::Diagnostic,
+    evaluator::{environment::Environment, evaluator::eval_program, object::Object},
+    lexer::Lexer,
+    parser::parser::Parser,
+    token::TokenType,
+};
+
+use super::completer::CrustHelper;
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+const HISTORY_FILE: &str = ".crust_history";
+
+const HELP: &str = "\
+:tokens      toggle printing the tokens of each input
+:ast         toggle printing the parsed program of each input
+:time        toggle printing how long evaluation took
+:env         list the bindings in the session
+:load <file> run a script in the session
+:reset       clear all bindings
+:help        show this message";
 
 pub fn start() {
+    let mut editor: Editor<CrustHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("error: could not start the line editor: {}", error);
+            return;
+        }
+    };
+    let history = history_path();
+    if let Some(path) = &history {
+        // A missing history file just means this is the first session.
+        let _ = editor.load_history(path);
+    }
+
+    let mut session = Session::new();
+    editor.set_helper(Some(CrustHelper {
+        env: Rc::clone(&session.env),
+    }));
     let mut input = String::new();
 
     loop {
-        print!(">> ");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut input).unwrap();
-        let mut lexer = Lexer::new(&input);
-        loop {
-            let token = lexer.next_token();
-            if token.token_type == crate::token::TokenType::EOF {
+        let prompt = if input.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                input.push_str(&line);
+                input.push('\n');
+            }
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => break,
+            Err(error) => {
+                eprintln!("error: {}", error);
                 break;
             }
-            println!("{:?}", token);
+        }
+
+        let command = input.trim_start().starts_with(':');
+        if !command && is_incomplete(&input) {
+            continue;
+        }
+        if !input.trim().is_empty() {
+            let _ = editor.add_history_entry(input.trim_end());
+        }
+
+        if command {
+            session.command(input.trim());
+            // `:reset` replaces the environment the completer reads from.
+            if let Some(helper) = editor.helper_mut() {
+                helper.env = Rc::clone(&session.env);
+            }
+        } else {
+            session.run("<repl>", &input);
         }
         input.clear();
     }
+
+    if let Some(path) = &history {
+        if let Err(error) = editor.save_history(path) {
+            eprintln!(
+                "warning: could not save history to {}: {}",
+                path.display(),
+                error
+            );
+        }
+    }
+}
+
+/// Everything that lasts for a whole REPL session: the global environment
+/// and the debugging output toggled by meta-commands.
+struct Session {
+    env: Rc<RefCell<Environment>>,
+    show_tokens: bool,
+    show_ast: bool,
+    show_time: bool,
+}
+
+impl Session {
+    fn new() -> Session {
+        Session {
+            env: Environment::new(),
+            show_tokens: false,
+            show_ast: false,
+            show_time: false,
+        }
+    }
+
+    /// Parses and evaluates `source`; `name` identifies it in diagnostics.
+    fn run(&mut self, name: &str, source: &str) {
+        if self.show_tokens {
+            for token in Lexer::new(source).filter(|token| token.token_type != TokenType::EOF) {
+                println!("{:?}", token);
+            }
+        }
+
+        let mut parser = Parser::new(Lexer::new(source));
+        let program = parser.parse_program();
+
+        if !parser.errors.is_empty() {
+            for error in &parser.errors {
+                eprint!("{}", error.render(name, source));
+            }
+            return;
+        }
+        // `Display` fully parenthesises expressions, which shows how they parsed.
+        if self.show_ast {
+            for statement in &program.statements {
+                println!("{}", statement);
+            }
+        }
+
+        let start = Instant::now();
+        let result = eval_program(&program, &self.env);
+        let elapsed = start.elapsed();
+
+        match result {
+            Object::Error(error) => eprint!("{}", Diagnostic::from(error).render(name, source)),
+            // `let` statements and other valueless lines print nothing.
+            Object::Null => (),
+            result => println!("{}", result),
+        }
+        if self.show_time {
+            println!("evaluated in {:?}", elapsed);
+        }
+    }
+
+    /// Runs a `:command` line.
+    fn command(&mut self, line: &str) {
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+
+        match (command, argument) {
+            (":tokens", "") => self.show_tokens = toggle("token output", self.show_tokens),
+            (":ast", "") => self.show_ast = toggle("AST output", self.show_ast),
+            (":time", "") => self.show_time = toggle("timing", self.show_time),
+            (":env", "") => {
+                let env = self.env.borrow();
+                let mut bindings: Vec<_> = env.bindings().collect();
+                bindings.sort_by_key(|(name, _)| *name);
+                for (name, value) in bindings {
+                    println!("{} = {}", name, value);
+                }
+            }
+            (":load", "") => eprintln!("error: `:load` needs a file name"),
+            (":load", path) => match fs::read_to_string(path) {
+                Ok(source) => self.run(path, &source),
+                Err(error) => eprintln!("error: could not read {}: {}", path, error),
+            },
+            (":reset", "") => self.env = Environment::new(),
+            (":help", "") => println!("{}", HELP),
+            (":tokens" | ":ast" | ":time" | ":env" | ":reset" | ":help", _) => {
+                eprintln!("error: `{}` takes no arguments", command)
+            }
+            _ => eprintln!("error: unknown command `{}`, try `:help`", command),
+        }
+    }
+}
+
+fn toggle(what: &str, enabled: bool) -> bool {
+    println!("{} {}", what, if enabled { "off" } else { "on" });
+    !enabled
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Whether `input` has more opening than closing brackets, meaning the user
+/// is still typing a block, call or literal that spans lines.
+fn is_incomplete(input: &str) -> bool {
+    let mut depth = 0;
+
+    for token in Lexer::new(input) {
+        match token.token_type {
+            TokenType::LPAREN | TokenType::LBRACE | TokenType::LBRACKET => depth += 1,
+            TokenType::RPAREN | TokenType::RBRACE | TokenType::RBRACKET => depth -= 1,
+            _ => (),
+        }
+    }
+
+    depth > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::evaluator::object::Object;
+
+    use super::{is_incomplete, Session};
+
+    #[test]
+    fn incomplete_input() {
+        let tests = vec![
+            ("let x = 5;", false),
+            ("let f = fn(x) {", true),
+            ("let f = fn(x) {\n  x + 1\n", true),
+            ("let f = fn(x) {\n  x + 1\n};", false),
+            ("add(1,", true),
+            ("[1, 2,\n3]", false),
+            (r#""{""#, false),
+            ("// {", false),
+            // Too many closers is a parse error, not a reason to keep reading.
+            ("}", false),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(is_incomplete(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn session_keeps_bindings_until_reset() {
+        let mut session = Session::new();
+
+        session.run("<repl>", "let x = 5;");
+        assert_eq!(session.env.borrow().get("x"), Some(Object::Integer(5)));
+
+        session.command(":reset");
+        assert_eq!(session.env.borrow().get("x"), None);
+    }
+
+    #[test]
+    fn toggle_commands() {
+        let mut session = Session::new();
+
+        session.command(":tokens");
+        session.command(":ast");
+        session.command(":time");
+        assert!(session.show_tokens && session.show_ast && session.show_time);
+
+        session.command(":tokens");
+        session.command(":tokens extra");
+        assert!(!session.show_tokens);
+    }
+
+    #[test]
+    fn load_command() {
+        let path = env::temp_dir().join(format!("crust-load-{}.cr", std::process::id()));
+        fs::write(
+            &path,
+            "let double = fn(x) { x * 2 };\nlet y = double(21);\n",
+        )
+        .unwrap();
+
+        let mut session = Session::new();
+        session.command(&format!(":load {}", path.display()));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(session.env.borrow().get("y"), Some(Object::Integer(42)));
+    }
 }
@@ -1,7 +1,30 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq, Clone)]
-pub struct Token {
+pub struct Token<'a> {
     pub token_type: TokenType,
-    pub literal: String,
+    pub literal: &'a str,
+    pub span: Span,
+}
+
+/// A region of the source text. `start` and `end` are byte offsets, while
+/// `line` and `column` are 1-based and locate `start`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Extends this span so that it ends where `other` ends.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -10,15 +33,20 @@ pub enum TokenType {
     EOF,
     IDENT,
     INT,
+    FLOAT,
+    STRING,
     ASSIGN,
     PLUS,
     MINUS,
     COMMA,
+    COLON,
     SEMICOLON,
     LPAREN,
     RPAREN,
     LBRACE,
     RBRACE,
+    LBRACKET,
+    RBRACKET,
     FUNCTION,
     LET,
     FSLASH,
@@ -33,4 +61,61 @@ pub enum TokenType {
     FALSE,
     EQ,
     NOTEQ,
+    LESSEQ,
+    GREATEREQ,
+    PERCENT,
+    AND,
+    OR,
+    PLUSASSIGN,
+    MINUSASSIGN,
+    STARASSIGN,
+    FSLASHASSIGN,
+    PERCENTASSIGN,
+    COMMENT,
+    DOCCOMMENT,
+}
+
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            TokenType::ASSIGN => "=",
+            TokenType::PLUS => "+",
+            TokenType::MINUS => "-",
+            TokenType::COMMA => ",",
+            TokenType::COLON => ":",
+            TokenType::SEMICOLON => ";",
+            TokenType::LPAREN => "(",
+            TokenType::RPAREN => ")",
+            TokenType::LBRACE => "{",
+            TokenType::RBRACE => "}",
+            TokenType::LBRACKET => "[",
+            TokenType::RBRACKET => "]",
+            TokenType::FUNCTION => "fn",
+            TokenType::LET => "let",
+            TokenType::FSLASH => "/",
+            TokenType::BANG => "!",
+            TokenType::STAR => "*",
+            TokenType::LESS => "<",
+            TokenType::GREATER => ">",
+            TokenType::IF => "if",
+            TokenType::ELSE => "else",
+            TokenType::RETURN => "return",
+            TokenType::TRUE => "true",
+            TokenType::FALSE => "false",
+            TokenType::EQ => "==",
+            TokenType::NOTEQ => "!=",
+            TokenType::LESSEQ => "<=",
+            TokenType::GREATEREQ => ">=",
+            TokenType::PERCENT => "%",
+            TokenType::AND => "&&",
+            TokenType::OR => "||",
+            TokenType::PLUSASSIGN => "+=",
+            TokenType::MINUSASSIGN => "-=",
+            TokenType::STARASSIGN => "*=",
+            TokenType::FSLASHASSIGN => "/=",
+            TokenType::PERCENTASSIGN => "%=",
+            other => return write!(f, "{:?}", other),
+        };
+        write!(f, "{}", symbol)
+    }
 }